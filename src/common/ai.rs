@@ -0,0 +1,362 @@
+/* Enemy behaviour : a small state machine per enemy. Enemies sleep until
+they see a player or hear gunfire (see sound.rs), react after a short
+delay, chase their target around the walls (see pathfinding.rs) and attack
+when close enough. Getting hurt can interrupt them, and makes them turn on
+whoever did it, other monsters included. Only runs where the world is
+simulated (solo, host, local co-op). */
+
+use std::collections::HashSet;
+
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::common::enemies::{self, AttackDef, AttackKind};
+use crate::common::entity::{Entity, EntityType, EntityState, Transform};
+use crate::common::events::GameEvent;
+use crate::common::level::Level;
+use crate::common::pathfinding::{self, Cell, Navigation};
+use crate::common::physics::Physics;
+use crate::common::powerups::{PowerUp, INVISIBLE_AIM_ERROR};
+use crate::common::sound::{self, NOISE_RANGE};
+use crate::common::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AiState {
+    Idle,   // Waiting for a player to come into view
+    Alert,  // Saw a player, reacting
+    Chase,  // Moving toward the target
+    Attack, // Winding up an attack
+    Pain,   // Stunned after being hurt
+    Investigate, // Heard a noise, going to have a look
+}
+
+const PROJECTILE_RANGE: f64 = 20.0;
+const BITE_ARC: f64 = 90.0; // Degrees in front of a biting enemy
+// Seconds an enemy looks for the source of a noise before giving up
+const INVESTIGATE_TIME: f64 = 10.0;
+
+pub struct Ai;
+
+impl Ai {
+    pub fn update(world: &mut World, level: &Level, navigation: &Navigation, delta_time: f64) {
+        // Possible targets : living players, and monsters once one of them hurt
+        // another. Only players are looked for, monsters fight back
+        let targets: Vec<(u32, f64, f64)> = world.entities.values()
+            .filter(|e| e.active && matches!(e.entity_type, EntityType::Player | EntityType::Enemy))
+            .filter(|e| e.health > 0 && e.state != EntityState::Dying && e.state != EntityState::Dead)
+            .map(|e| (e.id, e.transform.x, e.transform.y))
+            .collect();
+        let players: Vec<(u32, f64, f64)> = world.get_players().iter()
+            .filter(|p| p.health > 0)
+            .map(|p| (p.id, p.transform.x, p.transform.y))
+            .collect();
+
+        let enemy_ids: Vec<u32> = world.entities.values()
+            .filter(|e| e.entity_type == EntityType::Enemy && e.active)
+            .map(|e| e.id)
+            .collect();
+
+        // Gunfire of this frame, and the cells that hear each shot
+        let mut noises: Vec<(Cell, HashSet<Cell>)> = Vec::new();
+        for event in world.events() {
+            if let GameEvent::WeaponFired { x, y, .. } = *event {
+                if let Some(origin) = sound::noise_origin(level, x, y) {
+                    if !noises.iter().any(|(o, _)| *o == origin) {
+                        noises.push((origin, sound::noise_area(level, origin, NOISE_RANGE)));
+                    }
+                }
+            }
+        }
+
+        let skill = world.skill;
+        let mut events = Vec::new();
+        let mut attacks = Vec::new();
+        let mut summons = Vec::new();
+
+        for id in enemy_ids {
+            let Some(enemy) = world.entities.get_mut(&id) else { continue };
+            if enemy.state == EntityState::Dying || enemy.state == EntityState::Dead {
+                continue;
+            }
+
+            let Some(def) = enemy.definition() else { continue };
+
+            // Bosses change behaviour as they get hurt, every phase passed calls its minions
+            let phase = def.phase_at(enemy.health);
+            if phase > enemy.phase {
+                for reached in enemy.phase + 1..=phase {
+                    let Some(reached) = def.phase(reached) else { continue };
+                    if let Some(speed) = reached.speed {
+                        enemy.speed = speed;
+                    }
+                    if let Some(summon) = &reached.summon {
+                        summons.push((id, summon.as_str(), reached.summon_count, enemy.target_id));
+                    }
+                }
+                enemy.phase = phase;
+                // The attack being wound up belongs to the old phase
+                if enemy.ai_state == AiState::Attack {
+                    enemy.ai_state = AiState::Chase;
+                    if enemy.state == EntityState::Attacking {
+                        enemy.state = EntityState::Idle;
+                    }
+                }
+            }
+            enemy.attack_cooldown = (enemy.attack_cooldown - delta_time).max(0.0);
+            enemy.ai_timer -= delta_time;
+
+            let (x, y) = (enemy.transform.x, enemy.transform.y);
+            let target = enemy.target_id
+                .filter(|&t| t != id)
+                .and_then(|t| targets.iter().find(|p| p.0 == t))
+                .copied();
+
+            // Closest shot this enemy heard
+            let heard = noises.iter()
+                .filter(|(_, area)| area.contains(&pathfinding::cell_of(x, y)))
+                .map(|(origin, _)| pathfinding::cell_center(*origin))
+                .min_by(|a, b| (a.0 - x).hypot(a.1 - y).total_cmp(&(b.0 - x).hypot(b.1 - y)));
+
+            let mut step = None;
+            match enemy.ai_state {
+                AiState::Idle | AiState::Investigate => {
+                    if let Some(seen) = Self::nearest_visible(level, &players, x, y, def.sight_range) {
+                        enemy.target_id = Some(seen.0);
+                        enemy.ai_state = AiState::Alert;
+                        enemy.ai_timer = def.reaction_time * skill.reaction_scale();
+                        enemy.noise = None;
+                        enemy.noise_step = None;
+                    } else if let Some(noise) = heard {
+                        // A newer shot moves the search
+                        enemy.ai_state = AiState::Investigate;
+                        enemy.ai_timer = INVESTIGATE_TIME;
+                        enemy.noise = Some(noise);
+                    } else if enemy.ai_state == AiState::Investigate {
+                        match enemy.noise {
+                            Some((nx, ny)) if enemy.ai_timer > 0.0 && (nx - x).hypot(ny - y) > 0.5 => {
+                                let (gx, gy) = if level.has_line_of_sight(x, y, nx, ny) {
+                                    (nx, ny)
+                                } else {
+                                    pathfinding::next_step(level, &mut enemy.noise_step, pathfinding::cell_of(x, y), pathfinding::cell_of(nx, ny))
+                                        .map_or((nx, ny), pathfinding::cell_center)
+                                };
+                                Self::turn_toward(enemy, gx, gy, def.turn_speed * delta_time);
+                                let to_goal = (gx - x).hypot(gy - y).max(1e-6);
+                                let travel = (enemy.speed * skill.monster_speed_scale() * delta_time).min(to_goal);
+                                step = Some(((gx - x) / to_goal * travel, (gy - y) / to_goal * travel));
+                            }
+                            // Nothing there, or took too long : back to waiting
+                            _ => {
+                                enemy.ai_state = AiState::Idle;
+                                enemy.noise = None;
+                                enemy.noise_step = None;
+                            }
+                        }
+                    }
+                }
+                AiState::Alert => {
+                    if let Some((_, tx, ty)) = target {
+                        Self::turn_toward(enemy, tx, ty, def.turn_speed * delta_time);
+                    }
+                    if enemy.ai_timer <= 0.0 {
+                        enemy.ai_state = AiState::Chase;
+                    }
+                }
+                AiState::Chase => {
+                    // Lost the target (dead or left) : back to the nearest player
+                    let target = match target.or_else(|| Self::nearest(&players, x, y)) {
+                        Some(t) => t,
+                        None => {
+                            enemy.ai_state = AiState::Idle;
+                            enemy.target_id = None;
+                            continue;
+                        }
+                    };
+                    enemy.target_id = Some(target.0);
+                    let (_, tx, ty) = target;
+                    Self::turn_toward(enemy, tx, ty, def.turn_speed * delta_time);
+
+                    let distance = ((tx - x).powi(2) + (ty - y).powi(2)).sqrt();
+                    let attack = def.choose_attack(enemy.phase, distance)
+                        .filter(|_| enemy.attack_cooldown <= 0.0 && level.has_line_of_sight(x, y, tx, ty));
+                    if let Some(attack) = attack {
+                        enemy.ai_state = AiState::Attack;
+                        enemy.ai_timer = def.attacks(enemy.phase)[attack].windup;
+                        enemy.current_attack = attack;
+                        if enemy.state == EntityState::Idle {
+                            enemy.state = EntityState::Attacking;
+                        }
+                    } else if distance > def.max_range(enemy.phase) * 0.8 || def.closes_in(enemy.phase) {
+                        // Ranged enemies stop once in range, melee ones keep closing in.
+                        // Walk straight at a visible target, around the walls otherwise
+                        let (gx, gy) = if level.has_line_of_sight(x, y, tx, ty) {
+                            (tx, ty)
+                        } else {
+                            navigation.waypoint(level, target.0, (x, y), (tx, ty), &mut enemy.chase_step).unwrap_or((tx, ty))
+                        };
+                        let to_goal = ((gx - x).powi(2) + (gy - y).powi(2)).sqrt().max(1e-6);
+                        let travel = (enemy.speed * skill.monster_speed_scale() * delta_time).min((distance - def.radius).max(0.0));
+                        step = Some(((gx - x) / to_goal * travel, (gy - y) / to_goal * travel));
+                    }
+                }
+                AiState::Attack => {
+                    if let Some((_, tx, ty)) = target {
+                        Self::turn_toward(enemy, tx, ty, def.turn_speed * delta_time);
+                    }
+                    if enemy.ai_timer <= 0.0 {
+                        let Some(attack) = def.attacks(enemy.phase).get(enemy.current_attack) else { continue };
+                        enemy.ai_state = AiState::Chase;
+                        enemy.attack_cooldown = attack.cooldown * skill.cooldown_scale();
+                        if enemy.state == EntityState::Attacking {
+                            enemy.state = EntityState::Idle;
+                        }
+                        if let Some((target_id, _, _)) = target {
+                            attacks.push((id, target_id, attack));
+                            events.push(GameEvent::EnemyAttack {
+                                x,
+                                y,
+                                angle: enemy.transform.angle,
+                                attacker: id,
+                                target: target_id,
+                                melee: attack.kind == AttackKind::Melee,
+                            });
+                        }
+                    }
+                }
+                AiState::Pain => {
+                    if enemy.ai_timer <= 0.0 {
+                        enemy.ai_state = AiState::Chase;
+                    }
+                }
+            }
+
+            if let Some((dx, dy)) = step {
+                Physics::slide_entity(world, id, dx, dy, def.radius, level);
+            }
+        }
+
+        for (attacker, target_id, attack) in attacks {
+            Self::release_attack(world, level, attacker, target_id, attack);
+        }
+
+        for (summoner, id, count, target) in summons {
+            Self::summon(world, level, summoner, id, count, target);
+        }
+
+        for event in events {
+            world.push_event(event);
+        }
+    }
+
+    // The hit at the end of the wind-up. Bites land on whoever is still in front
+    // of the jaws, shots go toward where the target is now
+    fn release_attack(world: &mut World, level: &Level, attacker: u32, target_id: u32, attack: &AttackDef) {
+        let damage = (attack.damage as f64 * world.skill.damage_scale()).round() as i32;
+        if attack.kind == AttackKind::Melee {
+            Physics::melee(world, level, attacker, attack.range, BITE_ARC, damage);
+            return;
+        }
+
+        let Some(enemy) = world.get_entity(attacker) else { return };
+        let from = enemy.transform;
+        let radius = Physics::body_radius(enemy);
+        let Some(target) = world.get_entity(target_id) else { return };
+        let to = target.transform;
+        let mut rng = rand::thread_rng();
+        // Invisible targets are only guessed at
+        let aim_error = if target.has_effect(PowerUp::Invisibility) {
+            rng.gen_range(-INVISIBLE_AIM_ERROR..=INVISIBLE_AIM_ERROR)
+        } else {
+            0.0
+        };
+        let aim = (to.y - from.y).atan2(to.x - from.x).to_degrees() + aim_error;
+        let projectile_speed = attack.projectile_speed * world.skill.projectile_speed_scale();
+
+        for shot in 0..attack.shots {
+            match attack.kind {
+                // Fanned out evenly around the aim
+                AttackKind::Ranged => {
+                    let angle = aim + (shot as f64 - (attack.shots - 1) as f64 / 2.0) * attack.spread;
+                    let origin = Transform::new(
+                        from.x + angle.to_radians().cos() * radius,
+                        from.y + angle.to_radians().sin() * radius,
+                        angle,
+                    );
+                    let projectile = world.spawn_projectile(attacker, origin, damage, PROJECTILE_RANGE, attack.projectile_sprite);
+                    if let Some(projectile) = world.get_entity_mut(projectile) {
+                        projectile.speed = projectile_speed;
+                    }
+                }
+                // Randomly off by up to half the spread
+                AttackKind::Hitscan => {
+                    let half_spread = attack.spread / 2.0;
+                    let angle = aim + if half_spread > 0.0 { rng.gen_range(-half_spread..half_spread) } else { 0.0 };
+                    Physics::hitscan(world, level, attacker, Transform::new(from.x, from.y, angle), attack.range, damage, true);
+                }
+                AttackKind::Melee => {}
+            }
+        }
+    }
+
+    // Call `count` enemies around a boss, already chasing its target. Spots
+    // inside walls are skipped, so fewer may show up in tight places
+    fn summon(world: &mut World, level: &Level, summoner: u32, id: &str, count: u32, target: Option<u32>) {
+        let Some(boss) = world.get_entity(summoner) else { return };
+        let (x, y) = (boss.transform.x, boss.transform.y);
+        let distance = Physics::body_radius(boss) + 0.8;
+        let Some(radius) = enemies::index_of(id).and_then(enemies::get).map(|def| def.radius) else { return };
+
+        for i in 0..count {
+            let angle = (i as f64 * 360.0 / count as f64 + 45.0).to_radians();
+            let (sx, sy) = (x + angle.cos() * distance, y + angle.sin() * distance);
+            if !Physics::can_occupy(sx, sy, radius, level) || !level.has_line_of_sight(x, y, sx, sy) {
+                continue;
+            }
+            let Some(minion) = world.spawn_enemy(sx, sy, id) else { continue };
+            if let Some(minion) = world.get_entity_mut(minion) {
+                minion.ai_state = AiState::Chase;
+                minion.target_id = target;
+            }
+        }
+    }
+
+    // React to a hit : maybe flinch, and always wake up and go after the attacker
+    pub fn on_damage(enemy: &mut Entity) {
+        let Some(def) = enemy.definition() else { return };
+        if let Some(attacker) = enemy.last_attacker.filter(|&a| a != enemy.id) {
+            enemy.target_id = Some(attacker);
+        }
+        if rand::thread_rng().gen_bool(def.pain_chance) {
+            enemy.ai_state = AiState::Pain;
+            enemy.ai_timer = def.pain_time;
+        } else if matches!(enemy.ai_state, AiState::Idle | AiState::Alert | AiState::Investigate) {
+            enemy.ai_state = AiState::Chase;
+        }
+    }
+
+    fn nearest(players: &[(u32, f64, f64)], x: f64, y: f64) -> Option<(u32, f64, f64)> {
+        players.iter()
+            .min_by(|a, b| {
+                let da = (a.1 - x).powi(2) + (a.2 - y).powi(2);
+                let db = (b.1 - x).powi(2) + (b.2 - y).powi(2);
+                da.total_cmp(&db)
+            })
+            .copied()
+    }
+
+    fn nearest_visible(level: &Level, players: &[(u32, f64, f64)], x: f64, y: f64, range: f64) -> Option<(u32, f64, f64)> {
+        let visible: Vec<(u32, f64, f64)> = players.iter()
+            .filter(|p| (p.1 - x).powi(2) + (p.2 - y).powi(2) <= range * range)
+            .filter(|p| level.has_line_of_sight(x, y, p.1, p.2))
+            .copied()
+            .collect();
+        Self::nearest(&visible, x, y)
+    }
+
+    // Rotate toward a point, at most max_turn degrees
+    fn turn_toward(enemy: &mut Entity, x: f64, y: f64, max_turn: f64) {
+        let wanted = (y - enemy.transform.y).atan2(x - enemy.transform.x).to_degrees();
+        let diff = (wanted - enemy.transform.angle + 540.0).rem_euclid(360.0) - 180.0;
+        enemy.transform.angle = (enemy.transform.angle + diff.clamp(-max_turn, max_turn)).rem_euclid(360.0);
+    }
+}
@@ -0,0 +1,65 @@
+/* Game data files under data/ : a copy is built into the game, and a file at
+the same path in the working directory overrides it. Each file is read once
+at startup so mistakes show before the game starts. */
+
+use std::io::{ErrorKind, Result};
+use std::sync::OnceLock;
+
+pub struct DataFile<T: 'static> {
+    pub path: &'static str,
+    built_in: &'static str,
+    // Parses and checks the text, the origin names it in error messages
+    parse: fn(&str, &str) -> Result<T>,
+    loaded: OnceLock<(T, u64)>,
+}
+
+impl<T> DataFile<T> {
+    pub const fn new(path: &'static str, built_in: &'static str, parse: fn(&str, &str) -> Result<T>) -> Self {
+        Self { path, built_in, parse, loaded: OnceLock::new() }
+    }
+
+    pub fn load(&self) -> Result<()> {
+        let (text, origin) = match std::fs::read_to_string(self.path) {
+            Ok(text) => (text, self.path.to_string()),
+            Err(e) if e.kind() == ErrorKind::NotFound => (self.built_in.to_string(), format!("built-in {}", self.path)),
+            Err(e) => return Err(e),
+        };
+        let data = (self.parse)(&text, &origin)?;
+        let _ = self.loaded.set((data, text_hash(&text)));
+        Ok(())
+    }
+
+    fn loaded(&self) -> &(T, u64) {
+        self.loaded.get_or_init(|| {
+            let data = (self.parse)(self.built_in, &format!("built-in {}", self.path)).expect("built-in data files are valid");
+            (data, text_hash(self.built_in))
+        })
+    }
+
+    // Text of the copy built into the game, the tests check it parses
+    #[cfg(test)]
+    pub fn built_in(&self) -> &'static str {
+        self.built_in
+    }
+
+    // Loaded data. Falls back to the built-in copy
+    pub fn get(&self) -> &T {
+        &self.loaded().0
+    }
+
+    // Hash of the text the data was read from, the host and its clients must
+    // agree on it since entities refer to definitions by index
+    pub fn hash(&self) -> u64 {
+        self.loaded().1
+    }
+}
+
+// Hash of every data file the network game depends on
+pub fn definitions_hash() -> u64 {
+    crate::weapon::WEAPONS.hash() ^ crate::common::enemies::ENEMIES.hash().rotate_left(32)
+}
+
+// FNV-1a, stable across builds unlike the std hashers
+fn text_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
@@ -0,0 +1,220 @@
+/* Enemy definitions : stats, attacks and drops of every monster, read from
+data/enemies.toml like the weapons. A new monster only needs an entry there,
+and a sprite set in sprites.rs if it doesn't reuse one. */
+
+use std::io::{Error, ErrorKind, Result};
+use serde::Deserialize;
+
+use crate::common::data::DataFile;
+use crate::common::entity::SpriteType;
+use crate::common::pickups::PickupKind;
+
+pub static ENEMIES: DataFile<Vec<EnemyDef>> = DataFile::new("data/enemies.toml", include_str!("../../data/enemies.toml"), parse_definitions);
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttackKind {
+    Melee,   // Bite or claw in a cone in front of the enemy
+    Ranged,  // Projectiles thrown at the target
+    Hitscan, // Instant shots
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttackDef {
+    pub kind: AttackKind,
+    pub range: f64,
+    pub damage: i32,
+    pub windup: f64,   // Time spent in Attack before the hit lands
+    pub cooldown: f64,
+    #[serde(default = "default_shots")]
+    pub shots: u32,
+    #[serde(default)]
+    pub spread: f64,
+    #[serde(default = "default_projectile_sprite")]
+    pub projectile_sprite: SpriteType,
+    #[serde(default = "default_projectile_speed")]
+    pub projectile_speed: f64,
+}
+
+fn default_shots() -> u32 {
+    1
+}
+
+fn default_projectile_sprite() -> SpriteType {
+    SpriteType::ProjectileFireball
+}
+
+fn default_projectile_speed() -> f64 {
+    5.0
+}
+
+// Item an enemy may leave behind
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DropDef {
+    pub item: PickupKind,
+    pub chance: f64,
+}
+
+// Boss behaviour below a share of its health
+#[derive(Debug, Clone, Deserialize)]
+pub struct PhaseDef {
+    pub health_below: f64,
+    pub speed: Option<f64>,
+    // Enemy id and how many of them are called in when the phase starts
+    pub summon: Option<String>,
+    #[serde(default)]
+    pub summon_count: u32,
+    // Replace the base attacks when not empty
+    #[serde(default, rename = "attack")]
+    pub attacks: Vec<AttackDef>,
+}
+
+// One entry of the enemies file, see data/enemies.toml for the fields
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyDef {
+    pub id: String,
+    pub name: String,
+    pub sprite: SpriteType,
+    pub health: i32,
+    pub speed: f64,
+    pub radius: f64,
+    pub turn_speed: f64,
+    pub sight_range: f64,
+    pub reaction_time: f64,
+    pub pain_chance: f64,
+    pub pain_time: f64,
+    #[serde(default)]
+    pub score: u32,
+    // Bosses get a health bar, and killing them all opens the level's gates
+    #[serde(default)]
+    pub boss: bool,
+    #[serde(default, rename = "attack")]
+    pub attacks: Vec<AttackDef>,
+    #[serde(default, rename = "drop")]
+    pub drops: Vec<DropDef>,
+    #[serde(default, rename = "phase")]
+    pub phases: Vec<PhaseDef>,
+}
+
+impl EnemyDef {
+    // Phase for the given health : 0 is the base behaviour, n is phases[n - 1]
+    pub fn phase_at(&self, health: i32) -> usize {
+        let share = health as f64 / self.health as f64;
+        self.phases.iter().take_while(|p| share < p.health_below).count()
+    }
+
+    pub fn phase(&self, phase: usize) -> Option<&PhaseDef> {
+        phase.checked_sub(1).and_then(|i| self.phases.get(i))
+    }
+
+    // Attacks used in a phase
+    pub fn attacks(&self, phase: usize) -> &[AttackDef] {
+        match self.phase(phase) {
+            Some(p) if !p.attacks.is_empty() => &p.attacks,
+            _ => &self.attacks,
+        }
+    }
+
+    // Longest reach, ranged enemies stop closing in a bit before it
+    pub fn max_range(&self, phase: usize) -> f64 {
+        self.attacks(phase).iter().map(|a| a.range).fold(0.0, f64::max)
+    }
+
+    // Enemies with a melee attack keep walking up to their target
+    pub fn closes_in(&self, phase: usize) -> bool {
+        self.attacks(phase).iter().any(|a| a.kind == AttackKind::Melee)
+    }
+
+    // Index in attacks(phase) of the shortest attack that reaches `distance`
+    pub fn choose_attack(&self, phase: usize, distance: f64) -> Option<usize> {
+        self.attacks(phase).iter()
+            .enumerate()
+            .filter(|(_, a)| a.range >= distance)
+            .min_by(|a, b| a.1.range.total_cmp(&b.1.range))
+            .map(|(i, _)| i)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EnemyFile {
+    enemy: Vec<EnemyDef>,
+}
+
+fn parse_definitions(text: &str, origin: &str) -> Result<Vec<EnemyDef>> {
+    let invalid = |message: String| Error::new(ErrorKind::InvalidData, format!("{}: {}", origin, message));
+    let file = toml::from_str::<EnemyFile>(text).map_err(|e| invalid(e.to_string()))?;
+    for def in &file.enemy {
+        if def.health <= 0 || def.attacks.is_empty() {
+            return Err(invalid(format!("enemy {} needs health and at least one attack", def.id)));
+        }
+        if def.radius <= 0.0 || def.radius >= 0.5 {
+            return Err(invalid(format!("enemy {} must have a radius between 0 and 0.5", def.id)));
+        }
+        let mut chances = std::iter::once(def.pain_chance).chain(def.drops.iter().map(|d| d.chance));
+        if chances.any(|c| !(0.0..=1.0).contains(&c)) {
+            return Err(invalid(format!("enemy {} has a chance outside 0..1", def.id)));
+        }
+        // Phases go down in health, and only summon enemies that exist
+        let mut above = 1.0;
+        for phase in &def.phases {
+            if phase.health_below <= 0.0 || phase.health_below >= above {
+                return Err(invalid(format!("enemy {} phases must go down in health between 1 and 0", def.id)));
+            }
+            above = phase.health_below;
+            if let Some(summon) = &phase.summon {
+                if !file.enemy.iter().any(|other| &other.id == summon) {
+                    return Err(invalid(format!("enemy {} summons unknown enemy {}", def.id, summon)));
+                }
+            }
+        }
+    }
+    Ok(file.enemy)
+}
+
+pub fn definitions() -> &'static [EnemyDef] {
+    ENEMIES.get()
+}
+
+pub fn get(index: usize) -> Option<&'static EnemyDef> {
+    definitions().get(index)
+}
+
+// Position of an enemy in the definitions, how entities refer to their definition
+pub fn index_of(id: &str) -> Option<usize> {
+    definitions().iter().position(|def| def.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_definitions_are_valid() {
+        let defs = parse_definitions(ENEMIES.built_in(), "built-in enemies").unwrap();
+        for id in ["zombieman", "imp", "demon", "cacodemon", "boss"] {
+            assert!(defs.iter().any(|def| def.id == id), "missing {}", id);
+        }
+    }
+
+    #[test]
+    fn shortest_reaching_attack_is_chosen() {
+        let caco = &definitions()[index_of("cacodemon").unwrap()];
+        let bite = caco.choose_attack(0, 0.5).unwrap();
+        assert_eq!(caco.attacks[bite].kind, AttackKind::Melee);
+        let ball = caco.choose_attack(0, 5.0).unwrap();
+        assert_eq!(caco.attacks[ball].kind, AttackKind::Ranged);
+        assert_eq!(caco.choose_attack(0, caco.max_range(0) + 1.0), None);
+    }
+
+    #[test]
+    fn boss_phases_follow_its_health() {
+        let boss = &definitions()[index_of("boss").unwrap()];
+        assert!(boss.boss);
+        assert_eq!(boss.phase_at(boss.health), 0);
+        assert_eq!(boss.phase_at(boss.health / 2), 1);
+        assert_eq!(boss.phase_at(1), boss.phases.len());
+        // Phases without attacks of their own keep the base ones
+        assert_eq!(boss.attacks(1).len(), boss.phases[0].attacks.len());
+        assert_eq!(boss.attacks(2).len(), boss.attacks.len());
+    }
+}
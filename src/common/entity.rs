@@ -3,6 +3,11 @@ projectiles*/
 
 use std::io::Result;
 use serde::{Serialize, Deserialize};
+use crate::common::ai::{Ai, AiState};
+use crate::common::enemies::{self, EnemyDef};
+use crate::common::pickups::PickupKind;
+use crate::common::pathfinding::PathStep;
+use crate::common::powerups::{Effect, PowerUp, BERSERK_SPEED};
 
 // structure that stores coordinates of the entity
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -24,20 +29,63 @@ pub enum EntityType {
     Player,
     Enemy,
     Projectile,
+    Pickup,
+    Corpse, // Dead monster left lying around, drawn but never hit
 }
 
 pub const PLAYER_SPEED: f64 = 3.0;
 pub const PLAYER_ROTATION_SPEED: f64 = 50.0;
-pub const ENEMY_SPEED: f64 = 0.1;
 pub const PROJECTILE_SPEED: f64 = 10.0;
 pub const PLAYER_HEALTH: i32 = 100;
-pub const ENEMY_HEALTH: i32 = 50;
 pub const PROJECTILE_HEALTH: i32 = 1;
 
+// Side of an entity, projectiles only hurt the other side
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Team {
+    Players,
+    Monsters,
+}
+
+// Explosion of a projectile when it hits something
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Splash {
+    pub radius: f64,
+    pub knockback: f64, // Speed given to entities at the center, units per second
+}
+
+// Armor soaks up part of every hit until it is worn out
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ArmorTier {
+    None,
+    Green,
+    Blue,
+}
+
+impl ArmorTier {
+    // Fraction of the damage taken by the armor
+    pub fn absorption(self) -> f64 {
+        match self {
+            ArmorTier::None => 0.0,
+            ArmorTier::Green => 1.0 / 3.0,
+            ArmorTier::Blue => 0.5,
+        }
+    }
+
+    // Armor points given by the pickup of this tier
+    pub fn max_armor(self) -> i32 {
+        match self {
+            ArmorTier::None => 0,
+            ArmorTier::Green => 100,
+            ArmorTier::Blue => 200,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum EntityState {
     Idle,
     Hit,
+    Attacking, // Enemy winding up an attack, drawn with its attack frame
     Dying,
     Dead,
 }
@@ -58,16 +106,65 @@ pub struct Entity {
     pub max_distance: f64,
     pub distance_traveled: f64,
     pub damage: i32,
+    // Enemy behaviour, see common/ai.rs
+    pub ai_state: AiState,
+    pub ai_timer: f64,
+    pub attack_cooldown: f64,
+    pub target_id: Option<u32>,
+    // Where an investigating enemy heard a noise
+    pub noise: Option<(f64, f64)>,
+    // Next cells on the way to the noise and to a target without a flow
+    // field (another monster), see pathfinding::next_step
+    #[serde(skip)]
+    pub noise_step: Option<PathStep>,
+    #[serde(skip)]
+    pub chase_step: Option<PathStep>,
+    // Last entity that hurt this one, used by the death camera
+    pub last_attacker: Option<u32>,
+    pub team: Team,
+    // Entity that fired this projectile
+    pub owner_id: Option<u32>,
+    pub armor: i32,
+    pub armor_tier: ArmorTier,
+    // Push received from explosions, integrated and damped by Physics
+    pub velocity: (f64, f64),
+    // Projectiles that explode on impact
+    pub splash: Option<Splash>,
+    // Index in the enemy definitions, enemies only
+    pub enemy_def: Option<usize>,
+    // Attack being wound up, index in the definition's attacks
+    pub current_attack: usize,
+    // Boss phase reached, see EnemyDef::phase_at
+    pub phase: usize,
+    // Item given when touched, pickups only
+    pub pickup: Option<PickupKind>,
+    // Power-ups running on this entity, see common/powerups.rs
+    pub effects: Vec<Effect>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SpriteType {
     None,
+    Player,
     EnemyImp,
     EnemyDemon,
+    EnemyZombieman,
+    EnemyCacodemon,
+    EnemyBoss,
     ProjectilePistol,
     ProjectileShotgun,
     ProjectileGatling,
+    ProjectileFireball,
+    ProjectileRocket,
+    PickupHealth,
+    PickupArmor,
+    PickupArmorBlue,
+    PickupAmmo,
+    PickupWeapon,
+    PickupBerserk,
+    PickupInvulnerability,
+    PickupInvisibility,
+    PickupQuadDamage,
 }
 
 impl Entity {
@@ -79,31 +176,71 @@ impl Entity {
             speed: PLAYER_SPEED,
             health: PLAYER_HEALTH,
             active: true,
-            sprite_type: SpriteType::None,
+            sprite_type: SpriteType::Player,
             animation_timer: 0.0,
             current_frame: 0,
             state: EntityState::Idle,
             max_distance: 0.0,
             distance_traveled: 0.0,
             damage: 0,
+            ai_state: AiState::Idle,
+            ai_timer: 0.0,
+            attack_cooldown: 0.0,
+            target_id: None,
+            noise: None,
+            noise_step: None,
+            chase_step: None,
+            last_attacker: None,
+            team: Team::Players,
+            owner_id: None,
+            armor: 0,
+            armor_tier: ArmorTier::None,
+            velocity: (0.0, 0.0),
+            splash: None,
+            enemy_def: None,
+            current_attack: 0,
+            phase: 0,
+            pickup: None,
+            effects: Vec::new(),
         }
     }
 
-    pub fn new_enemy(id: u32, x: f64, y: f64, sprite_type: SpriteType) -> Self {
+    // Enemy of the given definition, see World::spawn_enemy to spawn one by id
+    pub fn new_enemy(id: u32, x: f64, y: f64, enemy_def: usize) -> Self {
+        let def = &enemies::definitions()[enemy_def];
         Self {
             id,
             entity_type: EntityType::Enemy,
             transform: Transform::new(x, y, 0.0),
-            speed: ENEMY_SPEED,
-            health: ENEMY_HEALTH,
+            speed: def.speed,
+            health: def.health,
             active: true,
-            sprite_type,
+            sprite_type: def.sprite,
             animation_timer: 0.0,
             current_frame: 0,
             state: EntityState::Idle,
             max_distance: 0.0,
             distance_traveled: 0.0,
             damage: 0,
+            ai_state: AiState::Idle,
+            ai_timer: 0.0,
+            attack_cooldown: 0.0,
+            target_id: None,
+            noise: None,
+            noise_step: None,
+            chase_step: None,
+            last_attacker: None,
+            team: Team::Monsters,
+            owner_id: None,
+            armor: 0,
+            armor_tier: ArmorTier::None,
+            velocity: (0.0, 0.0),
+            splash: None,
+            enemy_def: Some(enemy_def),
+            current_attack: 0,
+            phase: 0,
+            pickup: None,
+            effects: Vec::new(),
         }
     }
 
@@ -122,15 +259,72 @@ impl Entity {
             max_distance,
             distance_traveled: 0.0,
             damage,
+            ai_state: AiState::Idle,
+            ai_timer: 0.0,
+            attack_cooldown: 0.0,
+            target_id: None,
+            noise: None,
+            noise_step: None,
+            chase_step: None,
+            last_attacker: None,
+            team: Team::Players,
+            owner_id: None,
+            armor: 0,
+            armor_tier: ArmorTier::None,
+            velocity: (0.0, 0.0),
+            splash: None,
+            enemy_def: None,
+            current_attack: 0,
+            phase: 0,
+            pickup: None,
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn new_pickup(id: u32, x: f64, y: f64, kind: PickupKind) -> Self {
+        Self {
+            pickup: Some(kind),
+            entity_type: EntityType::Pickup,
+            sprite_type: kind.sprite_type(),
+            speed: 0.0,
+            ..Self::new_player(id, x, y)
+        }
+    }
+
+    // What is left of a monster : its sprite in the death frame, at the same place
+    pub fn new_corpse(id: u32, enemy: &Entity) -> Self {
+        Self {
+            entity_type: EntityType::Corpse,
+            transform: enemy.transform,
+            sprite_type: enemy.sprite_type,
+            state: EntityState::Dead,
+            team: Team::Monsters,
+            speed: 0.0,
+            health: 0,
+            ..Self::new_player(id, enemy.transform.x, enemy.transform.y)
         }
     }
 
+    // Definition of an enemy, None for anything else
+    pub fn definition(&self) -> Option<&'static EnemyDef> {
+        self.enemy_def.and_then(enemies::get)
+    }
+
     pub fn take_damage(&mut self, amount: i32) {
         if self.state == EntityState::Dying || self.state == EntityState::Dead {
             return;
         }
+        if self.has_effect(PowerUp::Invulnerability) {
+            return;
+        }
 
-        self.health -= amount;
+        // Armor takes its share first, the rest goes to health
+        let absorbed = ((amount as f64 * self.armor_tier.absorption()) as i32).min(self.armor);
+        self.armor -= absorbed;
+        if self.armor == 0 {
+            self.armor_tier = ArmorTier::None;
+        }
+        self.health -= amount - absorbed;
         if self.health <= 0 {
             self.state = EntityState::Dying;
             self.current_frame = 0;
@@ -139,6 +333,72 @@ impl Entity {
             self.state = EntityState::Hit;
             self.current_frame = 0; // Reset frame to show hit effect immediately
             self.animation_timer = 0.0;
+            if self.entity_type == EntityType::Enemy {
+                Ai::on_damage(self);
+            }
+        }
+    }
+
+    // Apply the health and armor of an item, false if it was of no use
+    pub fn pick_up(&mut self, kind: PickupKind) -> bool {
+        let mut used = false;
+        if kind.health() > 0 && self.health < PLAYER_HEALTH {
+            self.health = (self.health + kind.health()).min(PLAYER_HEALTH);
+            used = true;
+        }
+        // Armor is replaced only by a better amount, as in Doom
+        if let Some(tier) = kind.armor() {
+            if self.armor < tier.max_armor() {
+                self.armor = tier.max_armor();
+                self.armor_tier = tier;
+                used = true;
+            }
+        }
+        if let PickupKind::PowerUp(power_up) = kind {
+            self.give_effect(power_up);
+            used = true;
+        }
+        used
+    }
+
+    pub fn has_effect(&self, power_up: PowerUp) -> bool {
+        self.effects.iter().any(|e| e.power_up == power_up)
+    }
+
+    // Start a power-up, or start it over if it is already running
+    pub fn give_effect(&mut self, power_up: PowerUp) {
+        self.effects.retain(|e| e.power_up != power_up);
+        self.effects.push(Effect { power_up, remaining: power_up.duration() });
+    }
+
+    // Count the power-ups down, dropping the ones that ran out
+    pub fn update_effects(&mut self, delta_time: f64) {
+        for effect in self.effects.iter_mut() {
+            effect.remaining -= delta_time;
         }
+        self.effects.retain(|e| e.remaining > 0.0);
+    }
+
+    // Movement speed multiplier of the running power-ups
+    pub fn speed_scale(&self) -> f64 {
+        if self.has_effect(PowerUp::Berserk) { BERSERK_SPEED } else { 1.0 }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_absorbs_its_share_until_worn_out() {
+        let mut player = Entity::new_player(1, 1.5, 2.5);
+        assert!(player.pick_up(PickupKind::GreenArmor));
+        assert!(!player.pick_up(PickupKind::GreenArmor));
+        player.take_damage(30);
+        assert_eq!((player.health, player.armor), (80, 90));
+
+        player.armor = 5;
+        player.take_damage(30);
+        assert_eq!((player.health, player.armor, player.armor_tier), (55, 0, ArmorTier::None));
+    }
+}
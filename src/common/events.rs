@@ -0,0 +1,31 @@
+use serde::{Serialize, Deserialize};
+use crate::common::entity::SpriteType;
+use crate::common::pickups::PickupKind;
+use crate::weapon::SoundCue;
+
+// Gameplay events raised by the simulation. They are not part of the world
+// state: modes drain them every frame to drive effects (particles, ...) and
+// the host forwards them to clients.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum GameEvent {
+    // A projectile stopped against a wall, angle is its travel direction
+    WallImpact { x: f64, y: f64, angle: f64 },
+    // An entity took damage but survived
+    EntityHit { x: f64, y: f64, sprite_type: SpriteType },
+    // An entity started dying
+    EntityDeath { x: f64, y: f64, sprite_type: SpriteType },
+    // A weapon was fired from (x, y) towards angle
+    WeaponFired { x: f64, y: f64, angle: f64 },
+    // A weapon made a sound, its name is in the sounds of definition `weapon`
+    WeaponSound { x: f64, y: f64, weapon: usize, cue: SoundCue },
+    // A hitscan shot stopped against a wall at (x, y)
+    BulletPuff { x: f64, y: f64 },
+    // An enemy finished winding up an attack against target
+    EnemyAttack { x: f64, y: f64, angle: f64, attacker: u32, target: u32, melee: bool },
+    // A projectile exploded, hurting everything in radius
+    Explosion { x: f64, y: f64, radius: f64 },
+    // A monster finished dying, credited to whoever hurt it last
+    EnemyKilled { killer: u32 },
+    // A player took an item, whoever controls that player applies ammo and weapons
+    ItemPickup { x: f64, y: f64, player_id: u32, kind: PickupKind },
+}
@@ -1,6 +1,4 @@
 use std::collections::HashSet;
-use std::fs::OpenOptions;
-use std::io::Write;
 use device_query::{DeviceQuery, DeviceState, Keycode};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -16,7 +14,9 @@ pub enum Action {
     SwitchWeapon1,
     SwitchWeapon2,
     SwitchWeapon3,
-    RespawnEnemies,
+    SwitchWeapon4,
+    SwitchWeapon5,
+    NextWave, // Survival : end the break early
     RespawnPlayer,
     ToggleFPS,
     Sprint,
@@ -24,8 +24,130 @@ pub enum Action {
     None,
 }
 
+// Mapping from keys to actions, several keys can trigger the same action
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    pub keys: Vec<(Keycode, Action)>,
+}
+
+impl KeyBindings {
+    pub fn default_keys() -> Self {
+        Self {
+            keys: vec![
+                // Movement
+                (Keycode::Up, Action::MoveForward),
+                (Keycode::W, Action::MoveForward),
+                (Keycode::Down, Action::MoveBackward),
+                (Keycode::S, Action::MoveBackward),
+                (Keycode::Left, Action::StrafeLeft),
+                (Keycode::A, Action::StrafeLeft),
+                (Keycode::Right, Action::StrafeRight),
+                (Keycode::D, Action::StrafeRight),
+                // Rotation
+                (Keycode::Q, Action::RotateLeft),
+                (Keycode::E, Action::RotateRight),
+                // Switch weapon
+                (Keycode::Key1, Action::SwitchWeapon1),
+                (Keycode::Key2, Action::SwitchWeapon2),
+                (Keycode::Key3, Action::SwitchWeapon3),
+                (Keycode::Key4, Action::SwitchWeapon4),
+                (Keycode::Key5, Action::SwitchWeapon5),
+                // Actions
+                (Keycode::Space, Action::Shoot),
+                (Keycode::R, Action::Reload),
+                (Keycode::Enter, Action::RespawnPlayer),
+                (Keycode::P, Action::NextWave),
+                (Keycode::F, Action::ToggleFPS),
+                (Keycode::LShift, Action::Sprint),
+                (Keycode::RShift, Action::Sprint),
+            ],
+        }
+    }
+
+    // Bindings of the local players sharing one keyboard (split-screen)
+    pub fn local_player(index: usize) -> Self {
+        let keys = match index {
+            0 => vec![
+                (Keycode::W, Action::MoveForward),
+                (Keycode::S, Action::MoveBackward),
+                (Keycode::A, Action::StrafeLeft),
+                (Keycode::D, Action::StrafeRight),
+                (Keycode::Q, Action::RotateLeft),
+                (Keycode::E, Action::RotateRight),
+                (Keycode::Space, Action::Shoot),
+                (Keycode::Space, Action::RespawnPlayer),
+                (Keycode::R, Action::Reload),
+                (Keycode::Key1, Action::SwitchWeapon1),
+                (Keycode::Key2, Action::SwitchWeapon2),
+                (Keycode::Key3, Action::SwitchWeapon3),
+                (Keycode::Key4, Action::SwitchWeapon4),
+                (Keycode::Key5, Action::SwitchWeapon5),
+                (Keycode::LShift, Action::Sprint),
+            ],
+            1 => vec![
+                (Keycode::Up, Action::MoveForward),
+                (Keycode::Down, Action::MoveBackward),
+                (Keycode::Comma, Action::StrafeLeft),
+                (Keycode::Slash, Action::StrafeRight),
+                (Keycode::Left, Action::RotateLeft),
+                (Keycode::Right, Action::RotateRight),
+                (Keycode::Enter, Action::Shoot),
+                (Keycode::Enter, Action::RespawnPlayer),
+                (Keycode::Backspace, Action::Reload),
+                (Keycode::Key8, Action::SwitchWeapon1),
+                (Keycode::Key9, Action::SwitchWeapon2),
+                (Keycode::Key0, Action::SwitchWeapon3),
+                (Keycode::Minus, Action::SwitchWeapon4),
+                (Keycode::Equal, Action::SwitchWeapon5),
+                (Keycode::RShift, Action::Sprint),
+            ],
+            2 => vec![
+                (Keycode::I, Action::MoveForward),
+                (Keycode::K, Action::MoveBackward),
+                (Keycode::J, Action::StrafeLeft),
+                (Keycode::L, Action::StrafeRight),
+                (Keycode::U, Action::RotateLeft),
+                (Keycode::O, Action::RotateRight),
+                (Keycode::H, Action::Shoot),
+                (Keycode::H, Action::RespawnPlayer),
+                (Keycode::Y, Action::Reload),
+                (Keycode::Key6, Action::SwitchWeapon1),
+                (Keycode::Key7, Action::SwitchWeapon2),
+                (Keycode::T, Action::SwitchWeapon3),
+                (Keycode::G, Action::SwitchWeapon4),
+                (Keycode::V, Action::SwitchWeapon5),
+                (Keycode::B, Action::Sprint),
+            ],
+            _ => vec![
+                (Keycode::Numpad8, Action::MoveForward),
+                (Keycode::Numpad5, Action::MoveBackward),
+                (Keycode::Numpad4, Action::StrafeLeft),
+                (Keycode::Numpad6, Action::StrafeRight),
+                (Keycode::Numpad7, Action::RotateLeft),
+                (Keycode::Numpad9, Action::RotateRight),
+                (Keycode::Numpad0, Action::Shoot),
+                (Keycode::Numpad0, Action::RespawnPlayer),
+                (Keycode::NumpadAdd, Action::Reload),
+                (Keycode::Numpad1, Action::SwitchWeapon1),
+                (Keycode::Numpad2, Action::SwitchWeapon2),
+                (Keycode::Numpad3, Action::SwitchWeapon3),
+                (Keycode::NumpadMultiply, Action::SwitchWeapon4),
+                (Keycode::NumpadDivide, Action::SwitchWeapon5),
+                (Keycode::NumpadSubtract, Action::Sprint),
+            ],
+        };
+        Self { keys }
+    }
+
+    pub fn is_active(&self, pressed: &[Keycode], action: Action) -> bool {
+        self.keys.iter().any(|(key, a)| *a == action && pressed.contains(key))
+    }
+}
+
 pub struct InputManager {
     device_state: DeviceState,
+    bindings: KeyBindings,
+    pressed_keys: Vec<Keycode>,
     active_actions: HashSet<Action>,
 }
 
@@ -33,63 +155,22 @@ impl InputManager {
     pub fn new() -> Self {
         Self {
             device_state: DeviceState::new(),
+            bindings: KeyBindings::default_keys(),
+            pressed_keys: Vec::new(),
             active_actions: HashSet::new(),
         }
     }
 
     pub fn update(&mut self) {
         let keys: Vec<Keycode> = self.device_state.get_keys();
-        
+
         self.active_actions.clear();
 
         // Map keys to actions
-        if keys.contains(&Keycode::Up) || keys.contains(&Keycode::W) {
-            self.active_actions.insert(Action::MoveForward);
-        }
-        if keys.contains(&Keycode::Down) || keys.contains(&Keycode::S) {
-            self.active_actions.insert(Action::MoveBackward);
-        }
-        if keys.contains(&Keycode::Left) || keys.contains(&Keycode::A) {
-            self.active_actions.insert(Action::StrafeLeft);
-        }
-        if keys.contains(&Keycode::Right) || keys.contains(&Keycode::D) {
-            self.active_actions.insert(Action::StrafeRight);
-        }
-        
-        // Rotation
-        if keys.contains(&Keycode::Q) {
-            self.active_actions.insert(Action::RotateLeft);
-        }
-        if keys.contains(&Keycode::E) {
-            self.active_actions.insert(Action::RotateRight);
-        }
-
-        // Switch weapon
-        if keys.contains(&Keycode::Key1) {
-            self.active_actions.insert(Action::SwitchWeapon1);
-        }
-        if keys.contains(&Keycode::Key2) {
-            self.active_actions.insert(Action::SwitchWeapon2);
-        }
-        if keys.contains(&Keycode::Key3) {
-            self.active_actions.insert(Action::SwitchWeapon3);
-        }
-
-        // Actions
-        if keys.contains(&Keycode::Space) {
-            self.active_actions.insert(Action::Shoot);
-        }
-        if keys.contains(&Keycode::R) {
-            self.active_actions.insert(Action::Reload);
-        }
-        if keys.contains(&Keycode::P) {
-            self.active_actions.insert(Action::RespawnEnemies);
-        }
-        if keys.contains(&Keycode::F) {
-            self.active_actions.insert(Action::ToggleFPS);
-        }
-        if keys.contains(&Keycode::LShift) || keys.contains(&Keycode::RShift) {
-            self.active_actions.insert(Action::Sprint);
+        for (key, action) in &self.bindings.keys {
+            if keys.contains(key) {
+                self.active_actions.insert(*action);
+            }
         }
 
         // Quit: Ctrl + C
@@ -98,9 +179,16 @@ impl InputManager {
         if ctrl_pressed && keys.contains(&Keycode::C) {
             self.active_actions.insert(Action::Quit);
         }
+
+        self.pressed_keys = keys;
     }
 
     pub fn is_active(&self, action: Action) -> bool {
         self.active_actions.contains(&action)
     }
+
+    // Check an action against other bindings, used by local multiplayer
+    pub fn is_active_with(&self, bindings: &KeyBindings, action: Action) -> bool {
+        bindings.is_active(&self.pressed_keys, action)
+    }
 }
@@ -4,15 +4,193 @@ use crossterm::{
 	cursor::MoveTo,
 	style::{Print, Color, SetBackgroundColor},
 };
+use std::collections::HashSet;
   
 use crate::entity::Entity;
+use crate::common::pickups::PickupKind;
+use crate::common::powerups::PowerUp;
+use crate::common::skill::{SKILL_ALL, SKILL_EASY, SKILL_MEDIUM, SKILL_HARD};
+use crate::common::world::DEFAULT_CORPSE_LIMIT;
   
 pub struct Level {
 	pub layout: Vec<Vec<u8>>,
 	pub size: (u32, u32),
+	// What the level starts with, spawned by World::spawn_things
+	pub things: Vec<Thing>,
+	// Cells of the gates, walls that open once the bosses are dead
+	pub gates: Vec<(usize, usize)>,
+	// Where the monsters of survival waves come in
+	pub spawn_points: Vec<(f64, f64)>,
+	// Most dead monsters left lying around, 0 removes them as they die
+	pub corpse_limit: usize,
+}
+
+// Wall type of a closed gate
+pub const GATE_WALL: u8 = 6;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ThingKind {
+	Enemy(usize), // Index in the enemy definitions
+	Pickup(PickupKind),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Thing {
+	pub x: f64,
+	pub y: f64,
+	pub kind: ThingKind,
+	// Skill flags (skill.rs) of the skills the thing appears on
+	pub skills: u8,
+}
+
+impl Thing {
+	pub fn new(x: f64, y: f64, kind: ThingKind) -> Self {
+		Self { x, y, kind, skills: SKILL_ALL }
+	}
+
+	pub fn on_skills(self, skills: u8) -> Self {
+		Self { skills, ..self }
+	}
+
+	// Weapon lying on the floor, None if the weapon definitions don't have it
+	pub fn weapon(x: f64, y: f64, id: &str) -> Option<Self> {
+		let index = crate::weapon::index_of(id)?;
+		Some(Self::new(x, y, ThingKind::Pickup(PickupKind::Weapon(index))))
+	}
+
+	// Monster waiting in the level, None if the enemy definitions don't have it
+	pub fn enemy(x: f64, y: f64, id: &str) -> Option<Self> {
+		let index = crate::common::enemies::index_of(id)?;
+		Some(Self::new(x, y, ThingKind::Enemy(index)))
+	}
 }
   
 impl Level {
+	// Open or close the gates to follow World::gates_open. true if anything changed
+	pub fn set_gates_open(&mut self, open: bool) -> bool {
+		self.update_gates(open, &HashSet::new())
+	}
+
+	// Same, but a gate doesn't close on a cell in `occupied` : it waits for
+	// whatever stands there to leave instead of walling it in
+	pub fn update_gates(&mut self, open: bool, occupied: &HashSet<(usize, usize)>) -> bool {
+		let wall = if open { 0 } else { GATE_WALL };
+		let mut changed = false;
+		for &(x, y) in &self.gates {
+			if self.layout[y][x] != wall && (open || !occupied.contains(&(x, y))) {
+				self.layout[y][x] = wall;
+				changed = true;
+			}
+		}
+		changed
+	}
+
+	fn find_gates(layout: &[Vec<u8>]) -> Vec<(usize, usize)> {
+		layout.iter().enumerate()
+			.flat_map(|(y, row)| row.iter().enumerate().filter(|(_, &cell)| cell == GATE_WALL).map(move |(x, _)| (x, y)))
+			.collect()
+	}
+
+	// true if the point lies in an empty cell inside the map
+	pub fn is_empty(&self, x: f64, y: f64) -> bool {
+		if x < 0.0 || y < 0.0 {
+			return false;
+		}
+		let (grid_x, grid_y) = (x as usize, y as usize);
+		grid_y < self.layout.len() && grid_x < self.layout[grid_y].len() && self.layout[grid_y][grid_x] == 0
+	}
+
+	// true if no wall cell lies on the segment between the two points,
+	// walks the grid cell by cell (DDA)
+	pub fn has_line_of_sight(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> bool {
+		let (dx, dy) = (x1 - x0, y1 - y0);
+		let (mut cell_x, mut cell_y) = (x0.floor() as i64, y0.floor() as i64);
+		let (end_x, end_y) = (x1.floor() as i64, y1.floor() as i64);
+		let step_x = if dx >= 0.0 { 1 } else { -1 };
+		let step_y = if dy >= 0.0 { 1 } else { -1 };
+
+		// Fraction of the segment needed to cross one cell on each axis
+		let delta_x = if dx != 0.0 { (1.0 / dx).abs() } else { f64::INFINITY };
+		let delta_y = if dy != 0.0 { (1.0 / dy).abs() } else { f64::INFINITY };
+		let mut next_x = if dx >= 0.0 { (cell_x as f64 + 1.0 - x0) * delta_x } else { (x0 - cell_x as f64) * delta_x };
+		let mut next_y = if dy >= 0.0 { (cell_y as f64 + 1.0 - y0) * delta_y } else { (y0 - cell_y as f64) * delta_y };
+
+		loop {
+			if !self.is_empty(cell_x as f64 + 0.5, cell_y as f64 + 0.5) {
+				return false;
+			}
+			if cell_x == end_x && cell_y == end_y {
+				return true;
+			}
+			if next_x < next_y {
+				if next_x > 1.0 { return true; }
+				cell_x += step_x;
+				next_x += delta_x;
+			} else {
+				if next_y > 1.0 { return true; }
+				cell_y += step_y;
+				next_y += delta_y;
+			}
+		}
+	}
+
+	// Walk the grid along a ray (DDA) from (start_x, start_y) toward angle (degrees).
+	// Returns the distance along the ray to the first wall and its type, or
+	// (max_distance, 0) if there is no wall that close. Outside the map counts as wall type 1
+	pub fn cast_ray(&self, start_x: f64, start_y: f64, angle: f64, max_distance: f64) -> (f64, u8) {
+		let ray_dir_x = angle.to_radians().cos();
+		let ray_dir_y = angle.to_radians().sin();
+
+		let mut map_x = start_x.floor() as i32;
+		let mut map_y = start_y.floor() as i32;
+
+		// Delta distance calculation (distance ray has to travel to go from 1 x-side to the next x-side)
+		let delta_dist_x = if ray_dir_x == 0.0 { f64::INFINITY } else { (1.0 / ray_dir_x).abs() };
+		let delta_dist_y = if ray_dir_y == 0.0 { f64::INFINITY } else { (1.0 / ray_dir_y).abs() };
+
+		let (step_x, mut side_dist_x) = if ray_dir_x < 0.0 {
+			(-1, (start_x - map_x as f64) * delta_dist_x)
+		} else {
+			(1, (map_x as f64 + 1.0 - start_x) * delta_dist_x)
+		};
+		let (step_y, mut side_dist_y) = if ray_dir_y < 0.0 {
+			(-1, (start_y - map_y as f64) * delta_dist_y)
+		} else {
+			(1, (map_y as f64 + 1.0 - start_y) * delta_dist_y)
+		};
+
+		// DDA Loop : jump to the next map square, in x or in y
+		loop {
+			let distance;
+			if side_dist_x < side_dist_y {
+				distance = side_dist_x;
+				side_dist_x += delta_dist_x;
+				map_x += step_x;
+			} else {
+				distance = side_dist_y;
+				side_dist_y += delta_dist_y;
+				map_y += step_y;
+			}
+
+			if distance > max_distance {
+				return (max_distance, 0);
+			}
+
+			// Check if ray has hit a wall
+			let wall_type = if map_y >= 0 && map_x >= 0 {
+				self.layout.get(map_y as usize)
+					.and_then(|row| row.get(map_x as usize))
+					.copied()
+					.unwrap_or(1)
+			} else {
+				1 // Hit boundary
+			};
+			if wall_type > 0 {
+				return (distance, wall_type);
+			}
+		}
+	}
+
 	pub fn print(&self) -> Result<(), std::io::Error> {
 		let (width, height) = self.size;
 		for y in 0..height {
@@ -73,16 +251,63 @@ impl Level {
 		vec![1,4,4,4,4,4,4,4,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
 		vec![1,4,0,4,0,0,0,0,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
 		vec![1,4,0,0,0,0,5,0,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
-		vec![1,4,0,4,0,0,0,0,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
-		vec![1,4,0,4,4,4,4,4,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
-		vec![1,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
-		vec![1,4,4,4,4,4,4,4,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+		vec![1,4,0,4,0,0,0,0,4,0,0,0,0,0,0,0,0,0,0,0,1,1,1,1],
+		vec![1,4,0,4,4,4,4,4,4,0,0,0,0,0,0,0,0,0,0,0,1,0,0,1],
+		vec![1,4,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,6,0,0,1],
+		vec![1,4,4,4,4,4,4,4,4,0,0,0,0,0,0,0,0,0,0,0,1,0,0,1],
 	    vec![1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1]
 	];
-  
+
+	let mut things = vec![
+		Thing::new(5.5, 2.5, ThingKind::Pickup(PickupKind::AmmoClip)),
+		Thing::new(12.5, 2.5, ThingKind::Pickup(PickupKind::Medikit)),
+		Thing::new(14.5, 10.5, ThingKind::Pickup(PickupKind::AmmoShells)),
+		Thing::new(3.5, 12.5, ThingKind::Pickup(PickupKind::Stimpack)),
+		Thing::new(20.5, 12.5, ThingKind::Pickup(PickupKind::GreenArmor)),
+		Thing::new(18.5, 5.5, ThingKind::Pickup(PickupKind::AmmoRockets)),
+		// Reward for finding the closed room, along with the gatling
+		Thing::new(2.5, 16.5, ThingKind::Pickup(PickupKind::BlueArmor)),
+		// Power-ups : one for the way through, one before the boss
+		Thing::new(12.5, 14.5, ThingKind::Pickup(PickupKind::PowerUp(PowerUp::QuadDamage))),
+		Thing::new(8.5, 6.5, ThingKind::Pickup(PickupKind::PowerUp(PowerUp::Berserk))),
+		Thing::new(21.5, 16.5, ThingKind::Pickup(PickupKind::PowerUp(PowerUp::Invisibility))),
+		Thing::new(14.5, 18.5, ThingKind::Pickup(PickupKind::PowerUp(PowerUp::Invulnerability))),
+		// A little help on the easy skills
+		Thing::new(4.5, 9.5, ThingKind::Pickup(PickupKind::Stimpack)).on_skills(SKILL_EASY),
+	];
+	// Weapons the definitions file doesn't have are left out
+	let weapons = [
+		(8.5, 5.5, "shotgun"),
+		(20.5, 2.5, "rocket_launcher"),
+		(12.5, 20.5, "chainsaw"),
+		(4.5, 17.5, "gatling"),
+	];
+	things.extend(weapons.iter().filter_map(|&(x, y, id)| Thing::weapon(x, y, id)));
+	// Same for monsters
+	let enemies = [
+		(10.5, 10.5, "imp"),
+		(7.5, 7.5, "demon"),
+		(16.5, 2.5, "zombieman"),
+	];
+	things.extend(enemies.iter().filter_map(|&(x, y, id)| Thing::enemy(x, y, id)));
+	// Skipped on the easy skills, more company on the hard ones
+	let tougher = [
+		(16.5, 16.5, "cacodemon", SKILL_MEDIUM | SKILL_HARD),
+		(12.5, 14.5, "imp", SKILL_HARD),
+		(20.5, 8.5, "demon", SKILL_HARD),
+	];
+	things.extend(tougher.iter().filter_map(|&(x, y, id, skills)| Thing::enemy(x, y, id).map(|t| t.on_skills(skills))));
+	// Guards the gate of the exit room in the bottom right corner
+	things.extend(Thing::enemy(16.5, 20.5, "boss"));
+
+	let gates = Self::find_gates(&layout);
 	Ok(Self {
 		layout: layout,
 		size: (24, 24),
+		things,
+		gates,
+		spawn_points: vec![(22.5, 1.5), (22.5, 12.5), (12.5, 21.5), (1.5, 12.5)],
+		corpse_limit: DEFAULT_CORPSE_LIMIT,
 	})
 	}
 }
\ No newline at end of file
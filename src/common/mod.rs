@@ -3,4 +3,14 @@ pub mod level;
 pub mod world;
 pub mod input;
 pub mod physics;
-pub mod protocol;
\ No newline at end of file
+pub mod protocol;
+pub mod events;
+pub mod ai;
+pub mod pathfinding;
+pub mod pickups;
+pub mod enemies;
+pub mod skill;
+pub mod waves;
+pub mod sound;
+pub mod powerups;
+pub mod data;
@@ -0,0 +1,258 @@
+/* Navigation over the level grid. A* finds a path for a single enemy, flow
+fields give every cell the direction toward a player and are shared by all
+the monsters chasing that player. Moves are 8-connected, diagonals are only
+allowed when they don't cut a wall corner. */
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::common::level::Level;
+use crate::common::world::World;
+
+pub type Cell = (i32, i32);
+
+// Costs of a straight and a diagonal step, integers keep the heap simple
+const STRAIGHT: u32 = 10;
+const DIAGONAL: u32 = 14;
+
+const NEIGHBOURS: [(i32, i32, u32); 8] = [
+    (1, 0, STRAIGHT), (-1, 0, STRAIGHT), (0, 1, STRAIGHT), (0, -1, STRAIGHT),
+    (1, 1, DIAGONAL), (1, -1, DIAGONAL), (-1, 1, DIAGONAL), (-1, -1, DIAGONAL),
+];
+
+pub fn cell_of(x: f64, y: f64) -> Cell {
+    (x.floor() as i32, y.floor() as i32)
+}
+
+pub fn cell_center((x, y): Cell) -> (f64, f64) {
+    (x as f64 + 0.5, y as f64 + 0.5)
+}
+
+fn walkable(level: &Level, (x, y): Cell) -> bool {
+    let (cx, cy) = cell_center((x, y));
+    level.is_empty(cx, cy)
+}
+
+// Cells reachable in one step from `cell`, with the cost of the step
+fn neighbours(level: &Level, (x, y): Cell) -> impl Iterator<Item = (Cell, u32)> + '_ {
+    NEIGHBOURS.iter().filter_map(move |&(dx, dy, cost)| {
+        let next = (x + dx, y + dy);
+        let blocked_corner = dx != 0 && dy != 0
+            && (!walkable(level, (x + dx, y)) || !walkable(level, (x, y + dy)));
+        (walkable(level, next) && !blocked_corner).then_some((next, cost))
+    })
+}
+
+// Octile distance, admissible for 8-connected moves
+fn heuristic((ax, ay): Cell, (bx, by): Cell) -> u32 {
+    let (dx, dy) = ((ax - bx).unsigned_abs(), (ay - by).unsigned_abs());
+    STRAIGHT * dx.max(dy) + (DIAGONAL - STRAIGHT) * dx.min(dy)
+}
+
+// Shortest path between two cells, both ends included. None if unreachable
+pub fn find_path(level: &Level, start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+    if !walkable(level, start) || !walkable(level, goal) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Cell, Cell> = HashMap::new();
+    let mut cost: HashMap<Cell, u32> = HashMap::new();
+    cost.insert(start, 0);
+    open.push(Reverse((heuristic(start, goal), start)));
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == goal {
+            let mut path = vec![current];
+            let mut cell = current;
+            while let Some(&previous) = came_from.get(&cell) {
+                path.push(previous);
+                cell = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let current_cost = cost[&current];
+        for (next, step) in neighbours(level, current) {
+            let new_cost = current_cost + step;
+            if cost.get(&next).is_none_or(|&c| new_cost < c) {
+                cost.insert(next, new_cost);
+                came_from.insert(next, current);
+                open.push(Reverse((new_cost + heuristic(next, goal), next)));
+            }
+        }
+    }
+    None
+}
+
+// Next cell of a path searched with find_path, kept by the entity following
+// it so the search only runs again once either end moved to another cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathStep {
+    from: Cell,
+    goal: Cell,
+    next: Option<Cell>,
+}
+
+// Next cell from `from` toward `goal`, reusing `cached` when it still applies
+pub fn next_step(level: &Level, cached: &mut Option<PathStep>, from: Cell, goal: Cell) -> Option<Cell> {
+    match *cached {
+        Some(step) if step.from == from && step.goal == goal => step.next,
+        _ => {
+            let next = find_path(level, from, goal).and_then(|path| path.get(1).copied());
+            *cached = Some(PathStep { from, goal, next });
+            next
+        }
+    }
+}
+
+// Distance to a target cell from every reachable cell of the level
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    pub target: Cell,
+    distances: HashMap<Cell, u32>,
+}
+
+impl FlowField {
+    pub fn new(level: &Level, target: Cell) -> Self {
+        let mut distances = HashMap::new();
+        let mut open = BinaryHeap::new();
+        if walkable(level, target) {
+            distances.insert(target, 0);
+            open.push(Reverse((0, target)));
+        }
+
+        // Dijkstra from the target
+        while let Some(Reverse((distance, cell))) = open.pop() {
+            if distances.get(&cell).is_some_and(|&d| distance > d) {
+                continue;
+            }
+            for (next, step) in neighbours(level, cell) {
+                let new_distance = distance + step;
+                if distances.get(&next).is_none_or(|&d| new_distance < d) {
+                    distances.insert(next, new_distance);
+                    open.push(Reverse((new_distance, next)));
+                }
+            }
+        }
+
+        Self { target, distances }
+    }
+
+    pub fn distance(&self, cell: Cell) -> Option<u32> {
+        self.distances.get(&cell).copied()
+    }
+
+    // Next cell to go to from `cell`, None if unreachable or already there
+    pub fn next_cell(&self, level: &Level, cell: Cell) -> Option<Cell> {
+        let here = self.distance(cell)?;
+        neighbours(level, cell)
+            .filter_map(|(next, _)| self.distance(next).map(|d| (d, next)))
+            .filter(|(d, _)| *d < here)
+            .min()
+            .map(|(_, next)| next)
+    }
+}
+
+// Flow fields toward every player, kept by the modes that run the AI
+#[derive(Debug, Clone, Default)]
+pub struct Navigation {
+    fields: HashMap<u32, FlowField>,
+}
+
+impl Navigation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Recompute the field of players that changed cell, drop the ones of players that left
+    pub fn update(&mut self, world: &World, level: &Level) {
+        let players = world.get_players();
+        self.fields.retain(|id, _| players.iter().any(|p| p.id == *id));
+        for player in players {
+            let cell = cell_of(player.transform.x, player.transform.y);
+            if self.fields.get(&player.id).is_none_or(|f| f.target != cell) {
+                self.fields.insert(player.id, FlowField::new(level, cell));
+            }
+        }
+    }
+
+    pub fn field(&self, player_id: u32) -> Option<&FlowField> {
+        self.fields.get(&player_id)
+    }
+
+    // Point to walk toward to get from (x, y) to the entity standing at target,
+    // None if there is no way or we are already in its cell. `cached` is the
+    // step kept by the walker for targets without a field
+    pub fn waypoint(&self, level: &Level, target_id: u32, (x, y): (f64, f64), target: (f64, f64), cached: &mut Option<PathStep>) -> Option<(f64, f64)> {
+        let cell = cell_of(x, y);
+        match self.field(target_id) {
+            Some(field) => field.next_cell(level, cell),
+            // No field (a monster being fought, or a player who just joined) : plan a path on our own
+            None => next_step(level, cached, cell, cell_of(target.0, target.1)),
+        }
+        .map(cell_center)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::world::DEFAULT_CORPSE_LIMIT;
+
+    // 8x5 room split by a wall at x = 4, open only at the bottom row
+    fn split_room() -> Level {
+        let mut layout = vec![vec![0; 8]; 5];
+        layout[0].fill(1);
+        layout[4].fill(1);
+        for (y, row) in layout.iter_mut().enumerate() {
+            row[0] = 1;
+            row[7] = 1;
+            if y < 3 {
+                row[4] = 1;
+            }
+        }
+        Level { layout, size: (8, 5), things: Vec::new(), gates: Vec::new(), spawn_points: Vec::new(), corpse_limit: DEFAULT_CORPSE_LIMIT }
+    }
+
+    #[test]
+    fn path_goes_around_walls() {
+        let level = split_room();
+        let path = find_path(&level, (2, 1), (6, 1)).unwrap();
+        assert_eq!((path[0], path[path.len() - 1]), ((2, 1), (6, 1)));
+        assert!(path.contains(&(4, 3)));
+        assert!(path.windows(2).all(|pair| (pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1));
+        assert!(path.iter().all(|&cell| walkable(&level, cell)));
+
+        // The flow field agrees on the way out
+        let field = FlowField::new(&level, (6, 1));
+        assert_eq!(field.next_cell(&level, (2, 1)).map(|cell| field.distance(cell)), Some(field.distance(path[1])));
+    }
+
+    #[test]
+    fn next_step_is_kept_until_an_end_moves() {
+        let mut level = split_room();
+        let mut cached = None;
+        let first = next_step(&level, &mut cached, (2, 1), (6, 1));
+        assert!(first.is_some());
+
+        // Not searched again : the wall filling the opening goes unnoticed
+        level.layout[3][4] = 1;
+        assert_eq!(next_step(&level, &mut cached, (2, 1), (6, 1)), first);
+        assert_eq!(next_step(&level, &mut cached, (2, 2), (6, 1)), None);
+        assert_eq!(next_step(&level, &mut cached, (2, 2), (2, 3)), Some((2, 3)));
+    }
+
+    #[test]
+    fn no_path_through_closed_gates() {
+        let mut level = Level::debug_1().unwrap();
+        level.set_gates_open(false);
+        let (start, exit) = (cell_of(3.5, 3.5), cell_of(21.5, 20.5));
+        assert!(find_path(&level, start, exit).is_none());
+        assert!(FlowField::new(&level, exit).distance(start).is_none());
+
+        level.set_gates_open(true);
+        assert!(find_path(&level, start, exit).is_some());
+    }
+}
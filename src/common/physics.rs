@@ -1,64 +1,110 @@
+use rand::Rng;
+
 use crate::common::world::World;
-use crate::common::entity::{Entity, EntityType, EntityState, SpriteType};
+use crate::common::entity::{Entity, EntityType, EntityState, SpriteType, Splash, Team, Transform};
 use crate::common::level::Level;
+use crate::common::events::GameEvent;
+use crate::common::enemies::EnemyDef;
+use crate::common::pickups::{PickupKind, PICKUP_RADIUS};
+
+// Distance under which a projectile touches an entity, bigger monsters use their radius
+pub const HIT_RADIUS: f64 = 0.32;
+// Distance players keep from walls when pushed around
+const PLAYER_RADIUS: f64 = 0.2;
+// Distance monsters without a definition keep from walls
+const ENEMY_RADIUS: f64 = 0.3;
+// How fast knockback dies out, per second
+const KNOCKBACK_FRICTION: f64 = 4.0;
+// Distance between the items a monster drops
+const LOOT_SPREAD: f64 = 0.3;
 
 pub struct Physics;
 
 impl Physics {
     pub fn update(world: &mut World, delta_time: f64, level: &Level) -> u32 {
         let mut entities_to_remove = Vec::new();
-        let mut projectile_updates = Vec::new();
+        let mut events = Vec::new();
         let mut kills = 0;
+
+        Self::apply_velocities(world, delta_time, level);
         
-        // Collect projectile updates first
+        // Living entities projectiles can hit, and the hits found while sweeping
+        let targets: Vec<(u32, f64, f64, f64, Team, Option<usize>)> = world.entities.values()
+            .filter(|e| Self::can_be_hit(e) && e.state != EntityState::Dying && e.state != EntityState::Dead)
+            .map(|e| (e.id, e.transform.x, e.transform.y, Self::hit_radius(e), e.team, e.enemy_def))
+            .collect();
+        let mut hits = Vec::new();
+        let mut explosions = Vec::new();
+
+        // Move projectiles, sweeping the whole step so fast projectiles or
+        // long frames can't tunnel through walls or entities
         let entity_ids: Vec<u32> = world.entities.keys().cloned().collect();
         
         for id in entity_ids {
             if let Some(entity) = world.entities.get_mut(&id) {
-                if !entity.active {
+                if !entity.active || entity.entity_type != EntityType::Projectile {
                     continue;
                 }
 
-                match entity.entity_type {
-                    EntityType::Projectile => {
-                        let radians = entity.transform.angle.to_radians();
-                        let dist_step = entity.speed * delta_time;
-                        let new_x = entity.transform.x + radians.cos() * dist_step;
-                        let new_y = entity.transform.y + radians.sin() * dist_step;
-                        
-                        entity.distance_traveled += dist_step;
-
-                        // Check collision with walls
-                        if Self::can_move_to(new_x, new_y, level) {
-                            projectile_updates.push((entity.id, new_x, new_y));
-                        } else {
-                            // Projectile hit wall - mark for removal
-                            entities_to_remove.push(entity.id);
-                        }
-                        
-                        // Remove projectiles that travel too far
-                        if entity.distance_traveled >= entity.max_distance {
-                            entities_to_remove.push(entity.id);
-                        }
-                        
-                        // Remove projectiles that travel out of bounds (safety)
-                        if new_x < 0.0 || new_y < 0.0 || new_x > 24.0 || new_y > 24.0 {
+                let radians = entity.transform.angle.to_radians();
+                let dir = (radians.cos(), radians.sin());
+                let start = (entity.transform.x, entity.transform.y);
+                let dist_step = (entity.speed * delta_time)
+                    .min(entity.max_distance - entity.distance_traveled)
+                    .max(0.0);
+
+                // Walls through the grid, entities as circles, the closest one wins.
+                // The ray only looks at the cells after the start one, a shot fired
+                // point blank spawns inside the wall and hits it right away
+                let embedded = !level.is_empty(start.0, start.1);
+                let (wall_distance, _) = if embedded { (0.0, 0) } else { level.cast_ray(start.0, start.1, entity.transform.angle, dist_step) };
+                let reach = wall_distance.min(dist_step);
+                // Kind of monster that fired it, while it is still around
+                let owner_kind = targets.iter().find(|t| Some(t.0) == entity.owner_id).and_then(|t| t.5);
+                let hit = targets.iter()
+                    .filter(|t| Some(t.0) != entity.owner_id && Self::hostile(entity.team, owner_kind, t.4, t.5))
+                    .filter_map(|&(t_id, t_x, t_y, t_radius, _, _)| {
+                        segment_circle_hit(start, dir, reach, (t_x, t_y), t_radius).map(|d| (d, t_id))
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0));
+
+                if let Some((distance, t_id)) = hit {
+                    entity.transform.x = start.0 + dir.0 * distance;
+                    entity.transform.y = start.1 + dir.1 * distance;
+                    match entity.splash {
+                        // The explosion hurts the target, no extra impact damage
+                        Some(splash) => {
                             entities_to_remove.push(entity.id);
+                            explosions.push(((entity.transform.x, entity.transform.y), entity.damage, splash, entity.owner_id));
                         }
+                        None => hits.push((entity.id, t_id, entity.damage, entity.owner_id)),
+                    }
+                } else if embedded || wall_distance < dist_step {
+                    // Projectile hit wall - mark for removal, effects just in front of it
+                    entities_to_remove.push(entity.id);
+                    let mut impact = (wall_distance - 0.05).max(0.0);
+                    // Back out of the wall, on the side it was fired from
+                    while !level.is_empty(start.0 + dir.0 * impact, start.1 + dir.1 * impact) && impact > -1.0 {
+                        impact -= 0.05;
+                    }
+                    let (x, y) = (start.0 + dir.0 * impact, start.1 + dir.1 * impact);
+                    match entity.splash {
+                        Some(splash) => explosions.push(((x, y), entity.damage, splash, entity.owner_id)),
+                        None => events.push(GameEvent::WallImpact { x, y, angle: entity.transform.angle }),
+                    }
+                } else {
+                    entity.transform.x = start.0 + dir.0 * dist_step;
+                    entity.transform.y = start.1 + dir.1 * dist_step;
+                    entity.distance_traveled += dist_step;
+
+                    // Remove projectiles that travel too far
+                    if entity.distance_traveled >= entity.max_distance {
+                        entities_to_remove.push(entity.id);
                     }
-                    _ => {}
                 }
             }
         }
         
-        // Apply projectile position updates
-        for (id, new_x, new_y) in projectile_updates {
-            if let Some(entity) = world.entities.get_mut(&id) {
-                entity.transform.x = new_x;
-                entity.transform.y = new_y;
-            }
-        }
-        
         // Remove dead projectiles
         for id in &entities_to_remove {
             world.entities.remove(id);
@@ -66,8 +112,11 @@ impl Physics {
         entities_to_remove.clear();
 
         // Update animations and states
+        let mut boss_died = false;
+        let mut fallen = Vec::new();
         for entity in world.entities.values_mut() {
             if !entity.active { continue; }
+            entity.update_effects(delta_time);
 
             // Handle State Transitions
             match entity.state {
@@ -82,9 +131,19 @@ impl Physics {
                     entity.animation_timer += delta_time;
                     if entity.animation_timer >= 0.5 { // Death animation duration
                         entity.state = EntityState::Dead;
-                        entity.active = false; // Mark for removal
+                        // Dead players stay in the world until they respawn
+                        if entity.entity_type != EntityType::Player {
+                            entity.active = false; // Mark for removal
+                        }
                         if entity.entity_type == EntityType::Enemy {
                             kills += 1;
+                            if let Some(killer) = entity.last_attacker {
+                                events.push(GameEvent::EnemyKilled { killer });
+                            }
+                            if entity.definition().is_some_and(|def| def.boss) {
+                                boss_died = true;
+                            }
+                            fallen.push(entity.clone());
                         }
                     }
                 },
@@ -101,43 +160,23 @@ impl Physics {
             }
         }
 
-        // Collision Detection: Projectiles vs Enemies
-        let mut hits = Vec::new();
-        
-        // Collect active projectiles and enemies
-        let projectiles: Vec<(u32, f64, f64, i32)> = world.entities.values()
-            .filter(|e| e.entity_type == EntityType::Projectile && e.active)
-            .map(|e| (e.id, e.transform.x, e.transform.y, e.damage))
-            .collect();
-            
-        let enemies: Vec<(u32, f64, f64)> = world.entities.values()
-            .filter(|e| e.entity_type == EntityType::Enemy && e.active && e.state != EntityState::Dying && e.state != EntityState::Dead)
-            .map(|e| (e.id, e.transform.x, e.transform.y))
-            .collect();
-
-        for (p_id, p_x, p_y, p_damage) in projectiles {
-            for (e_id, e_x, e_y) in &enemies {
-                let dist_sq = (p_x - e_x).powi(2) + (p_y - e_y).powi(2);
-                if dist_sq < 0.1 { // Hit radius squared
-                    hits.push((p_id, *e_id, p_damage));
-                    break; // Projectile hits first enemy
-                }
-            }
-        }
-
         // Apply hits
-        for (p_id, e_id, damage) in hits {
+        for (p_id, t_id, damage, owner) in hits {
             // Remove projectile
             if let Some(proj) = world.entities.get_mut(&p_id) {
                 proj.active = false;
             }
             entities_to_remove.push(p_id);
 
-            // Damage enemy
-            if let Some(enemy) = world.entities.get_mut(&e_id) {
-                enemy.take_damage(damage);
+            // Damage target
+            if let Some(target) = world.entities.get_mut(&t_id) {
+                Self::hurt(target, damage, owner, &mut events);
             }
         }
+
+        for (center, damage, splash, owner) in explosions {
+            Self::explode(world, level, center, damage, splash, owner, &mut events);
+        }
         
         // Remove dead entities
         let mut dead_ids = Vec::new();
@@ -149,10 +188,234 @@ impl Physics {
         for id in dead_ids {
             world.entities.remove(&id);
         }
+
+        // Dead monsters leave a corpse and their loot, and get back up on
+        // nightmare. Bosses stay down
+        for enemy in fallen {
+            let corpse = world.spawn_corpse(&enemy);
+            let Some(def) = enemy.definition() else { continue };
+            Self::drop_loot(world, level, &enemy, def);
+            if let (Some(enemy_def), false) = (enemy.enemy_def, def.boss) {
+                world.queue_enemy_respawn(enemy.transform.x, enemy.transform.y, enemy_def, corpse);
+            }
+        }
+
+        // The last boss down opens the way out
+        if boss_died && !world.entities.values().any(|e| e.active && e.definition().is_some_and(|def| def.boss)) {
+            world.gates_open = true;
+        }
+
+        for event in events {
+            world.push_event(event);
+        }
+
+        // Bring back items taken in multiplayer
+        world.update_respawns(delta_time);
         
         kills
     }
 
+    // Roll every drop of a dead monster, the items are spread around where it
+    // fell, on its spot when that would put them in a wall
+    fn drop_loot(world: &mut World, level: &Level, enemy: &Entity, def: &EnemyDef) {
+        let mut rng = rand::thread_rng();
+        let (x, y) = (enemy.transform.x, enemy.transform.y);
+        let items: Vec<PickupKind> = def.drops.iter()
+            .filter(|drop| rng.gen_bool(drop.chance))
+            .map(|drop| drop.item)
+            .collect();
+        for (i, item) in items.into_iter().enumerate() {
+            let angle = i as f64 * 2.4;
+            let distance = if i == 0 { 0.0 } else { LOOT_SPREAD };
+            let (dx, dy) = (x + angle.cos() * distance, y + angle.sin() * distance);
+            let (dx, dy) = if level.is_empty(dx, dy) { (dx, dy) } else { (x, y) };
+            world.spawn_drop(dx, dy, item);
+        }
+    }
+
+    // Instant shot from `ray` (position and angle) up to `range`. Hurts the first
+    // hostile entity on the line, or leaves a puff on the wall if `puff` is set.
+    // Returns the id of the entity hit
+    pub fn hitscan(world: &mut World, level: &Level, shooter_id: u32, ray: Transform, range: f64, damage: i32, puff: bool) -> Option<u32> {
+        let (team, kind) = match world.get_entity(shooter_id) {
+            Some(shooter) => (shooter.team, shooter.enemy_def),
+            None => return None,
+        };
+        let radians = ray.angle.to_radians();
+        let dir = (radians.cos(), radians.sin());
+        let (wall_distance, _) = level.cast_ray(ray.x, ray.y, ray.angle, range);
+
+        let hit = world.entities.values()
+            .filter(|e| Self::can_be_hit(e) && e.id != shooter_id)
+            .filter(|e| Self::hostile(team, kind, e.team, e.enemy_def) && e.state != EntityState::Dying && e.state != EntityState::Dead)
+            .filter_map(|e| {
+                segment_circle_hit((ray.x, ray.y), dir, wall_distance, (e.transform.x, e.transform.y), Self::hit_radius(e)).map(|d| (d, e.id))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut events = Vec::new();
+        match hit {
+            Some((_, target_id)) => {
+                if let Some(target) = world.entities.get_mut(&target_id) {
+                    Self::hurt(target, damage, Some(shooter_id), &mut events);
+                }
+            }
+            None if puff && wall_distance < range => {
+                let impact = (wall_distance - 0.05).max(0.0);
+                events.push(GameEvent::BulletPuff { x: ray.x + dir.0 * impact, y: ray.y + dir.1 * impact });
+            }
+            None => {}
+        }
+        for event in events {
+            world.push_event(event);
+        }
+        hit.map(|(_, id)| id)
+    }
+
+    // Close range hit in a cone in front of the attacker : hurts the closest
+    // living entity of the other team, or the one it is fighting, within range
+    // and `arc` degrees
+    pub fn melee(world: &mut World, level: &Level, attacker_id: u32, range: f64, arc: f64, damage: i32) -> Option<u32> {
+        let attacker = world.get_entity(attacker_id)?;
+        let (x, y, angle, team) = (attacker.transform.x, attacker.transform.y, attacker.transform.angle, attacker.team);
+        let fighting = attacker.target_id;
+
+        let target = world.entities.values()
+            .filter(|e| Self::can_be_hit(e) && e.id != attacker_id && (e.team != team || Some(e.id) == fighting))
+            .filter(|e| e.state != EntityState::Dying && e.state != EntityState::Dead)
+            .filter_map(|e| {
+                let (dx, dy) = (e.transform.x - x, e.transform.y - y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                let off_angle = (dy.atan2(dx).to_degrees() - angle + 540.0).rem_euclid(360.0) - 180.0;
+                let in_cone = distance < 1e-6 || off_angle.abs() <= arc / 2.0;
+                (distance <= range + Self::hit_radius(e) && in_cone && level.has_line_of_sight(x, y, e.transform.x, e.transform.y))
+                    .then_some((distance, e.id))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, id)| id)?;
+
+        let mut events = Vec::new();
+        if let Some(entity) = world.entities.get_mut(&target) {
+            Self::hurt(entity, damage, Some(attacker_id), &mut events);
+        }
+        for event in events {
+            world.push_event(event);
+        }
+        Some(target)
+    }
+
+    // Hurt and push away everything within the splash radius that the blast
+    // can reach, the shooter included. Damage falls off with distance
+    pub fn explode(world: &mut World, level: &Level, (x, y): (f64, f64), damage: i32, splash: Splash, owner: Option<u32>, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::Explosion { x, y, radius: splash.radius });
+        for target in world.entities.values_mut() {
+            if !Self::can_be_hit(target) || target.state == EntityState::Dying || target.state == EntityState::Dead {
+                continue;
+            }
+            let (dx, dy) = (target.transform.x - x, target.transform.y - y);
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance >= splash.radius || !level.has_line_of_sight(x, y, target.transform.x, target.transform.y) {
+                continue;
+            }
+
+            let falloff = 1.0 - distance / splash.radius;
+            if distance > 1e-6 {
+                target.velocity.0 += dx / distance * splash.knockback * falloff;
+                target.velocity.1 += dy / distance * splash.knockback * falloff;
+            }
+            let amount = (damage as f64 * falloff).round() as i32;
+            if amount > 0 {
+                // Caught in its own blast : whoever hurt the shooter before keeps the blame
+                let attacker = owner.filter(|&id| id != target.id);
+                Self::hurt(target, amount, attacker, events);
+            }
+        }
+    }
+
+    // Move players and monsters by their velocity, which slowly dies out
+    fn apply_velocities(world: &mut World, delta_time: f64, level: &Level) {
+        let moving: Vec<(u32, (f64, f64), f64)> = world.entities.values()
+            .filter(|e| Self::can_be_hit(e) && (e.velocity.0 != 0.0 || e.velocity.1 != 0.0))
+            .map(|e| (e.id, e.velocity, Self::body_radius(e)))
+            .collect();
+
+        let damping = (-KNOCKBACK_FRICTION * delta_time).exp();
+        for (id, (vx, vy), radius) in moving {
+            Self::slide_entity(world, id, vx * delta_time, vy * delta_time, radius, level);
+            if let Some(entity) = world.entities.get_mut(&id) {
+                entity.velocity = (vx * damping, vy * damping);
+                if entity.velocity.0.hypot(entity.velocity.1) < 0.05 {
+                    entity.velocity = (0.0, 0.0);
+                }
+            }
+        }
+    }
+
+    // Distance a player or monster keeps from walls
+    pub fn body_radius(entity: &Entity) -> f64 {
+        match (entity.entity_type, entity.definition()) {
+            (EntityType::Player, _) => PLAYER_RADIUS,
+            (_, Some(def)) => def.radius,
+            _ => ENEMY_RADIUS,
+        }
+    }
+
+    // Distance under which shots touch an entity
+    fn hit_radius(entity: &Entity) -> f64 {
+        entity.definition().map_or(HIT_RADIUS, |def| def.radius.max(HIT_RADIUS))
+    }
+
+    // Whether shots of a side (and monster kind) hurt a target. Sides spare
+    // themselves, except monsters of different kinds : a stray shot between
+    // them starts a fight, as in Doom
+    fn hostile(team: Team, kind: Option<usize>, target_team: Team, target_kind: Option<usize>) -> bool {
+        target_team != team || (team == Team::Monsters && target_kind != kind)
+    }
+
+    // Only players and monsters stop shots, items and projectiles don't
+    fn can_be_hit(entity: &Entity) -> bool {
+        entity.active && matches!(entity.entity_type, EntityType::Player | EntityType::Enemy)
+    }
+
+    // Pairs of (player, pickup) touching each other this frame
+    pub fn touched_pickups(world: &World) -> Vec<(u32, u32)> {
+        let players: Vec<&Entity> = world.get_players().into_iter().filter(|p| p.health > 0).collect();
+        let mut touches = Vec::new();
+        for pickup in world.get_pickups() {
+            for player in &players {
+                let dist_sq = (pickup.transform.x - player.transform.x).powi(2) + (pickup.transform.y - player.transform.y).powi(2);
+                if dist_sq < PICKUP_RADIUS * PICKUP_RADIUS {
+                    touches.push((player.id, pickup.id));
+                    break; // First player to touch it gets it
+                }
+            }
+        }
+        touches
+    }
+
+    // Damage an entity and raise the matching event
+    pub fn hurt(target: &mut Entity, damage: i32, attacker: Option<u32>, events: &mut Vec<GameEvent>) {
+        if target.state == EntityState::Dying || target.state == EntityState::Dead {
+            return;
+        }
+        // Set first, hurt monsters turn on whoever did it
+        if attacker.is_some() {
+            target.last_attacker = attacker;
+        }
+        let before = (target.health, target.armor);
+        target.take_damage(damage);
+        // Nothing to show when the hit did nothing (invulnerability)
+        if (target.health, target.armor) == before {
+            return;
+        }
+        let (x, y, sprite_type) = (target.transform.x, target.transform.y, target.sprite_type);
+        if target.state == EntityState::Dying {
+            events.push(GameEvent::EntityDeath { x, y, sprite_type });
+        } else {
+            events.push(GameEvent::EntityHit { x, y, sprite_type });
+        }
+    }
+
     pub fn move_entity_forward(world: &mut World, entity_id: u32, distance: f64, level: &Level) -> bool {
         if let Some(entity) = world.entities.get(&entity_id).cloned() {
             let radians = entity.transform.angle.to_radians();
@@ -197,14 +460,380 @@ impl Physics {
         false
     }
 
+    // Move by (dx, dy), sliding along walls instead of stopping on them.
+    // The entity keeps `radius` away from walls
+    pub fn slide_entity(world: &mut World, entity_id: u32, dx: f64, dy: f64, radius: f64, level: &Level) -> bool {
+        let Some(entity) = world.entities.get_mut(&entity_id) else { return false };
+        let (x, y) = (entity.transform.x, entity.transform.y);
+        let mut moved = false;
+        if Self::can_occupy(x + dx, y, radius, level) {
+            entity.transform.x = x + dx;
+            moved = true;
+        }
+        if Self::can_occupy(entity.transform.x, y + dy, radius, level) {
+            entity.transform.y = y + dy;
+            moved = true;
+        }
+        moved
+    }
+
+    // true if a square of half size `radius` centered on (x, y) is free
+    pub fn can_occupy(x: f64, y: f64, radius: f64, level: &Level) -> bool {
+        [(-radius, -radius), (radius, -radius), (-radius, radius), (radius, radius)]
+            .iter()
+            .all(|(ox, oy)| level.is_empty(x + ox, y + oy))
+    }
+
     fn can_move_to(x: f64, y: f64, level: &Level) -> bool {
-        let grid_x = x as usize;
-        let grid_y = y as usize;
-        
-        if grid_x < level.layout[0].len() && grid_y < level.layout.len() {
-            level.layout[grid_y][grid_x] == 0
-        } else {
-            false
+        level.is_empty(x, y)
+    }
+}
+
+// Distance along a segment (start, unit dir, length) at which it first
+// enters a circle, None if it misses. 0 if it starts inside.
+pub fn segment_circle_hit(start: (f64, f64), dir: (f64, f64), length: f64, center: (f64, f64), radius: f64) -> Option<f64> {
+    let to_start = (start.0 - center.0, start.1 - center.1);
+    let c = to_start.0 * to_start.0 + to_start.1 * to_start.1 - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    // Solve |start + t * dir - center| = radius, dir has unit length
+    let b = to_start.0 * dir.0 + to_start.1 * dir.1;
+    let discriminant = b * b - c;
+    if b >= 0.0 || discriminant < 0.0 {
+        return None; // Moving away or passing beside
+    }
+    let t = -b - discriminant.sqrt();
+    (t <= length).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 12x5 room, with a one cell thick wall at x = 6 when `wall` is set
+    fn room(wall: bool) -> Level {
+        let mut layout = vec![vec![0; 12]; 5];
+        layout[0].fill(1);
+        layout[4].fill(1);
+        for row in layout.iter_mut() {
+            row[0] = 1;
+            row[11] = 1;
+            if wall {
+                row[6] = 1;
+            }
+        }
+        Level { layout, size: (12, 5), things: Vec::new(), gates: Vec::new(), spawn_points: Vec::new(), corpse_limit: crate::common::world::DEFAULT_CORPSE_LIMIT }
+    }
+
+    fn shoot(world: &mut World, shooter: u32, x: f64, y: f64, angle: f64) -> u32 {
+        world.spawn_projectile(shooter, Transform::new(x, y, angle), 10, 50.0, SpriteType::ProjectilePistol)
+    }
+
+    fn enemy_health(id: &str) -> i32 {
+        crate::common::enemies::definitions()[crate::common::enemies::index_of(id).unwrap()].health
+    }
+
+    #[test]
+    fn segment_circle_hit_finds_first_contact() {
+        let hit = segment_circle_hit((0.0, 0.0), (1.0, 0.0), 10.0, (5.0, 0.0), 0.5);
+        assert!((hit.unwrap() - 4.5).abs() < 1e-9);
+        // Too short, beside, behind, already inside
+        assert_eq!(segment_circle_hit((0.0, 0.0), (1.0, 0.0), 4.0, (5.0, 0.0), 0.5), None);
+        assert_eq!(segment_circle_hit((0.0, 0.0), (1.0, 0.0), 10.0, (5.0, 1.0), 0.5), None);
+        assert_eq!(segment_circle_hit((0.0, 0.0), (1.0, 0.0), 10.0, (-5.0, 0.0), 0.5), None);
+        assert_eq!(segment_circle_hit((5.1, 0.0), (1.0, 0.0), 10.0, (5.0, 0.0), 0.5), Some(0.0));
+    }
+
+    #[test]
+    fn cast_ray_stops_at_walls_and_max_distance() {
+        let level = room(true);
+        let (distance, wall_type) = level.cast_ray(1.5, 2.5, 0.0, 50.0);
+        assert!((distance - 4.5).abs() < 1e-9);
+        assert_eq!(wall_type, 1);
+        assert_eq!(level.cast_ray(1.5, 2.5, 0.0, 2.0), (2.0, 0));
+    }
+
+    #[test]
+    fn fast_projectile_does_not_tunnel_through_enemy() {
+        let level = room(false);
+        let mut world = World::new();
+        let player = world.spawn_entity(Entity::new_player(0, 1.5, 2.5));
+        let enemy = world.spawn_enemy(5.5, 2.5, "imp").unwrap();
+        let projectile = shoot(&mut world, player, 2.0, 2.5, 0.0);
+
+        // One huge frame : the projectile would land far behind the enemy
+        Physics::update(&mut world, 0.8, &level);
+
+        assert!(world.get_entity(enemy).unwrap().health < enemy_health("imp"));
+        assert!(world.get_entity(projectile).is_none());
+    }
+
+    #[test]
+    fn fast_projectile_does_not_tunnel_through_thin_wall() {
+        let level = room(true);
+        let mut world = World::new();
+        let player = world.spawn_entity(Entity::new_player(0, 1.5, 2.5));
+        let enemy = world.spawn_enemy(8.5, 2.5, "imp").unwrap();
+        let projectile = shoot(&mut world, player, 2.0, 2.5, 0.0);
+
+        Physics::update(&mut world, 2.0, &level);
+
+        assert_eq!(world.get_entity(enemy).unwrap().health, enemy_health("imp"));
+        assert!(world.get_entity(projectile).is_none());
+        assert!(matches!(world.drain_events()[..], [GameEvent::WallImpact { x, .. }] if x < 6.0));
+    }
+
+    #[test]
+    fn point_blank_rocket_does_not_pass_through_thin_wall() {
+        let level = room(true);
+        let mut world = World::new();
+        // Touching the wall at x = 6, the barrel is inside it
+        let player = world.spawn_entity(Entity::new_player(0, 5.7, 2.5));
+        let enemy = world.spawn_enemy(7.5, 2.5, "imp").unwrap();
+        let rocket = shoot(&mut world, player, 6.2, 2.5, 0.0);
+        world.get_entity_mut(rocket).unwrap().splash = Some(Splash { radius: 2.5, knockback: 8.0 });
+
+        Physics::update(&mut world, 0.1, &level);
+
+        assert_eq!(world.get_entity(enemy).unwrap().health, enemy_health("imp"));
+        assert!(world.get_entity(rocket).is_none());
+        assert!(world.drain_events().iter().any(|e| matches!(e, GameEvent::Explosion { x, .. } if *x < 6.0)));
+    }
+
+    #[test]
+    fn kills_go_to_the_shooter() {
+        let level = room(false);
+        let mut world = World::new();
+        world.spawn_entity(Entity::new_player(0, 1.5, 1.5));
+        let client = world.spawn_entity(Entity::new_player(0, 1.5, 2.5));
+        world.spawn_enemy(4.5, 2.5, "zombieman").unwrap();
+        let projectile = shoot(&mut world, client, 2.0, 2.5, 0.0);
+        world.get_entity_mut(projectile).unwrap().damage = 10_000;
+
+        let mut killers = Vec::new();
+        for _ in 0..10 {
+            Physics::update(&mut world, 0.1, &level);
+            killers.extend(world.drain_events().into_iter().filter_map(|e| match e {
+                GameEvent::EnemyKilled { killer } => Some(killer),
+                _ => None,
+            }));
         }
+        assert_eq!(killers, [client]);
+    }
+
+    #[test]
+    fn diagonal_projectile_does_not_slip_through_corner() {
+        // Two walls touching by a corner, a diagonal shot between them must stop
+        let mut level = room(false);
+        level.layout[1][4] = 1;
+        level.layout[2][3] = 1;
+        let mut world = World::new();
+        let player = world.spawn_entity(Entity::new_player(0, 1.5, 2.5));
+        let projectile = shoot(&mut world, player, 3.5, 1.5, 135.0);
+
+        Physics::update(&mut world, 1.0, &level);
+
+        assert!(world.get_entity(projectile).is_none());
+    }
+
+    #[test]
+    fn projectiles_spare_their_owner_and_team() {
+        let level = room(false);
+        let mut world = World::new();
+        let imp = world.spawn_enemy(2.0, 2.5, "imp").unwrap();
+        let other = world.spawn_enemy(5.5, 2.5, "imp").unwrap();
+        shoot(&mut world, imp, 2.0, 2.5, 0.0);
+
+        Physics::update(&mut world, 0.8, &level);
+
+        assert_eq!(world.get_entity(imp).unwrap().health, enemy_health("imp"));
+        assert_eq!(world.get_entity(other).unwrap().health, enemy_health("imp"));
+    }
+
+    #[test]
+    fn stray_shots_make_other_monsters_fight_back() {
+        let level = room(false);
+        let mut world = World::new();
+        let imp = world.spawn_enemy(2.0, 2.5, "imp").unwrap();
+        let demon = world.spawn_enemy(5.5, 2.5, "demon").unwrap();
+        shoot(&mut world, imp, 2.0, 2.5, 0.0);
+
+        Physics::update(&mut world, 0.8, &level);
+
+        let demon = world.get_entity(demon).unwrap();
+        assert!(demon.health < enemy_health("demon"));
+        assert_eq!(demon.target_id, Some(imp));
+        assert_ne!(demon.ai_state, crate::common::ai::AiState::Idle);
+    }
+
+    #[test]
+    fn hitscan_hits_first_enemy_and_stops_at_walls() {
+        let level = room(true);
+        let mut world = World::new();
+        let player = world.spawn_entity(Entity::new_player(0, 1.5, 2.5));
+        let far = world.spawn_enemy(4.5, 2.5, "imp").unwrap();
+        let near = world.spawn_enemy(3.5, 2.5, "imp").unwrap();
+        let behind_wall = world.spawn_enemy(8.5, 2.5, "imp").unwrap();
+
+        let hit = Physics::hitscan(&mut world, &level, player, Transform::new(1.5, 2.5, 0.0), 20.0, 10, true);
+        assert_eq!(hit, Some(near));
+
+        world.remove_entity(near);
+        world.remove_entity(far);
+        world.drain_events();
+        let hit = Physics::hitscan(&mut world, &level, player, Transform::new(1.5, 2.5, 0.0), 20.0, 10, true);
+        assert_eq!(hit, None);
+        assert_eq!(world.get_entity(behind_wall).unwrap().health, enemy_health("imp"));
+        assert!(matches!(world.drain_events()[..], [GameEvent::BulletPuff { .. }]));
+    }
+
+    #[test]
+    fn invulnerable_hits_raise_no_events() {
+        use crate::common::powerups::PowerUp;
+
+        let mut player = Entity::new_player(1, 1.5, 2.5);
+        player.give_effect(PowerUp::Invulnerability);
+        let mut events = Vec::new();
+        Physics::hurt(&mut player, 30, None, &mut events);
+        assert!(events.is_empty());
+        assert_eq!(player.health, 100);
+
+        player.effects.clear();
+        Physics::hurt(&mut player, 30, None, &mut events);
+        assert!(matches!(events[..], [GameEvent::EntityHit { .. }]));
+    }
+
+    #[test]
+    fn explosion_falls_off_pushes_and_stops_at_walls() {
+        let level = room(true);
+        let mut world = World::new();
+        let shooter = world.spawn_entity(Entity::new_player(0, 2.0, 2.5));
+        let close = world.spawn_enemy(3.5, 2.5, "imp").unwrap();
+        let behind_wall = world.spawn_enemy(7.0, 2.5, "imp").unwrap();
+        let splash = Splash { radius: 2.5, knockback: 8.0 };
+        world.get_entity_mut(shooter).unwrap().last_attacker = Some(behind_wall);
+
+        let mut events = Vec::new();
+        Physics::explode(&mut world, &level, (3.0, 2.5), 40, splash, Some(shooter), &mut events);
+
+        let health = |world: &World, id| world.get_entity(id).unwrap().health;
+        let full = enemy_health("imp");
+        assert_eq!(health(&world, close), full - 32);
+        assert_eq!(health(&world, shooter), crate::common::entity::PLAYER_HEALTH - 24);
+        assert_eq!(health(&world, behind_wall), full);
+        assert!(world.get_entity(close).unwrap().velocity.0 > 0.0);
+        assert!(world.get_entity(shooter).unwrap().velocity.0 < 0.0);
+        assert_eq!(world.get_entity(shooter).unwrap().last_attacker, Some(behind_wall));
+        assert_eq!(world.get_entity(close).unwrap().last_attacker, Some(shooter));
+
+        // Knockback moves the entity and dies out
+        let x = world.get_entity(close).unwrap().transform.x;
+        for _ in 0..60 {
+            Physics::update(&mut world, 1.0 / 30.0, &level);
+        }
+        let pushed = world.get_entity(close).unwrap();
+        assert!(pushed.transform.x > x);
+        assert_eq!(pushed.velocity, (0.0, 0.0));
+    }
+
+    #[test]
+    fn last_boss_down_opens_the_gates() {
+        let mut level = room(false);
+        level.layout[2][10] = crate::common::level::GATE_WALL;
+        level.gates.push((10, 2));
+        let mut world = World::new();
+        let boss = world.spawn_enemy(3.5, 2.5, "boss").unwrap();
+        world.spawn_enemy(5.5, 2.5, "imp").unwrap();
+
+        Physics::hurt(world.get_entity_mut(boss).unwrap(), 10_000, None, &mut Vec::new());
+        Physics::update(&mut world, 0.1, &level);
+        assert!(!world.gates_open, "still dying");
+        Physics::update(&mut world, 0.5, &level);
+        assert!(world.gates_open);
+
+        assert!(level.set_gates_open(world.gates_open));
+        assert!(level.is_empty(10.5, 2.5));
+        assert!(!level.set_gates_open(world.gates_open));
+    }
+
+    #[test]
+    fn restarting_does_not_close_a_gate_on_a_player() {
+        let mut level = room(false);
+        level.layout[2][10] = crate::common::level::GATE_WALL;
+        level.gates.push((10, 2));
+        let mut world = World::new();
+        let player = world.spawn_entity(Entity::new_player(0, 10.5, 2.5));
+        world.gates_open = true;
+        level.update_gates(world.gates_open, &world.occupied_cells());
+
+        // A new survival run starts with the gates closed
+        world.clear_level();
+        assert!(!level.update_gates(world.gates_open, &world.occupied_cells()));
+        assert!(level.is_empty(10.5, 2.5));
+
+        // It closes once the player is out of the way
+        world.respawn_player(player, 3.5, 2.5);
+        assert!(level.update_gates(world.gates_open, &world.occupied_cells()));
+        assert!(!level.is_empty(10.5, 2.5));
+    }
+
+    #[test]
+    fn dead_monsters_leave_corpses_and_loot() {
+        let level = room(false);
+        let mut world = World::new();
+        world.corpse_limit = 1;
+        let first = world.spawn_enemy(3.5, 2.5, "zombieman").unwrap();
+        let second = world.spawn_enemy(8.5, 2.5, "zombieman").unwrap();
+
+        for id in [first, second] {
+            Physics::hurt(world.get_entity_mut(id).unwrap(), 10_000, None, &mut Vec::new());
+            Physics::update(&mut world, 0.6, &level);
+        }
+
+        // Only the newest corpse is kept, it can't be shot
+        let corpses = world.get_corpses();
+        assert_eq!(corpses.len(), 1);
+        assert_eq!(corpses[0].transform.x, 8.5);
+        let player = world.spawn_entity(Entity::new_player(0, 6.5, 2.5));
+        let ray = Transform::new(6.5, 2.5, 0.0);
+        assert_eq!(Physics::hitscan(&mut world, &level, player, ray, 5.0, 10, false), None);
+        // Zombiemen always leave their clip, which doesn't come back once taken
+        world.respawn_pickups = true;
+        let clips: Vec<u32> = world.get_pickups().iter().map(|p| p.id).collect();
+        assert_eq!(clips.len(), 2);
+        for clip in clips {
+            world.take_pickup(clip);
+        }
+        world.update_respawns(1000.0);
+        assert!(world.get_pickups().is_empty());
+    }
+
+    #[test]
+    fn nightmare_respawns_follow_the_world_flag() {
+        let level = room(false);
+        for respawn_monsters in [true, false] {
+            let mut world = World::new();
+            world.skill = crate::common::skill::Skill::new(5);
+            world.respawn_monsters = respawn_monsters;
+            let imp = world.spawn_enemy(3.5, 2.5, "imp").unwrap();
+            Physics::hurt(world.get_entity_mut(imp).unwrap(), 10_000, None, &mut Vec::new());
+            Physics::update(&mut world, 0.6, &level);
+            world.update_respawns(1000.0);
+            assert_eq!(world.get_enemies().len(), respawn_monsters as usize);
+        }
+    }
+
+    #[test]
+    fn melee_hits_only_in_front_and_in_reach() {
+        let level = room(false);
+        let mut world = World::new();
+        let player = world.spawn_entity(Entity::new_player(0, 5.0, 2.5));
+        let behind = world.spawn_enemy(4.2, 2.5, "imp").unwrap();
+        assert_eq!(Physics::melee(&mut world, &level, player, 1.2, 60.0, 10), None);
+
+        let in_front = world.spawn_enemy(6.0, 2.7, "imp").unwrap();
+        assert_eq!(Physics::melee(&mut world, &level, player, 1.2, 60.0, 10), Some(in_front));
+        assert_eq!(world.get_entity(behind).unwrap().health, enemy_health("imp"));
     }
 }
@@ -0,0 +1,70 @@
+/* Items lying in the world : touching one applies it to the player if it is
+of any use (no medikit at full health). Health, armor and power-ups are kept by
+the player entity, ammo and weapons by the Player struct of whoever controls it. */
+
+use serde::{Serialize, Deserialize};
+use crate::common::entity::{ArmorTier, SpriteType};
+use crate::common::powerups::PowerUp;
+use crate::weapon::AmmoType;
+
+// Distance at which a player grabs an item
+pub const PICKUP_RADIUS: f64 = 0.6;
+// Delay before a taken item comes back, multiplayer modes only
+pub const PICKUP_RESPAWN_TIME: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PickupKind {
+    Stimpack,
+    Medikit,
+    GreenArmor,
+    BlueArmor,
+    AmmoClip,
+    AmmoShells,
+    AmmoRockets,
+    AmmoCells,
+    // Weapon at this index of the weapon definitions
+    Weapon(usize),
+    // Timed effect, kept by the player entity
+    PowerUp(PowerUp),
+}
+
+impl PickupKind {
+    pub fn sprite_type(self) -> SpriteType {
+        match self {
+            PickupKind::Stimpack | PickupKind::Medikit => SpriteType::PickupHealth,
+            PickupKind::GreenArmor => SpriteType::PickupArmor,
+            PickupKind::BlueArmor => SpriteType::PickupArmorBlue,
+            PickupKind::Weapon(_) => SpriteType::PickupWeapon,
+            PickupKind::PowerUp(power_up) => power_up.sprite_type(),
+            _ => SpriteType::PickupAmmo,
+        }
+    }
+
+    // Health restored, never above the player's starting health
+    pub fn health(self) -> i32 {
+        match self {
+            PickupKind::Stimpack => 10,
+            PickupKind::Medikit => 25,
+            _ => 0,
+        }
+    }
+
+    pub fn armor(self) -> Option<ArmorTier> {
+        match self {
+            PickupKind::GreenArmor => Some(ArmorTier::Green),
+            PickupKind::BlueArmor => Some(ArmorTier::Blue),
+            _ => None,
+        }
+    }
+
+    // Ammo given by ammo items, weapons give their own magazine
+    pub fn ammo(self) -> Option<(AmmoType, u32)> {
+        match self {
+            PickupKind::AmmoClip => Some((AmmoType::Bullets, 10)),
+            PickupKind::AmmoShells => Some((AmmoType::Shells, 4)),
+            PickupKind::AmmoRockets => Some((AmmoType::Rockets, 1)),
+            PickupKind::AmmoCells => Some((AmmoType::Cells, 20)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+/* Power-ups : items whose effect lasts a while. The effects running on a
+player are a list on its entity, counted down by the physics, so they travel
+with the world snapshots and every client sees them (an invisible player is
+drawn as a shimmer). */
+
+use serde::{Serialize, Deserialize};
+use crate::common::entity::SpriteType;
+
+// Damage multiplier of quad damage
+pub const QUAD_DAMAGE: f64 = 4.0;
+// Berserk : melee damage multiplier and movement speed multiplier
+pub const BERSERK_MELEE: f64 = 4.0;
+pub const BERSERK_SPEED: f64 = 1.3;
+// Degrees monsters may be off when aiming at an invisible player
+pub const INVISIBLE_AIM_ERROR: f64 = 25.0;
+// Seconds left when the HUD and the tint start blinking
+pub const RUNNING_OUT: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerUp {
+    Berserk,         // Faster, and melee hits much harder
+    Invulnerability, // No damage at all
+    Invisibility,    // Monsters aim badly at the player
+    QuadDamage,      // Every weapon hits harder
+}
+
+impl PowerUp {
+    // Seconds the effect lasts, taking the item again starts it over
+    pub fn duration(self) -> f64 {
+        match self {
+            PowerUp::Berserk => 30.0,
+            PowerUp::Invulnerability => 30.0,
+            PowerUp::Invisibility => 60.0,
+            PowerUp::QuadDamage => 30.0,
+        }
+    }
+
+    pub fn sprite_type(self) -> SpriteType {
+        match self {
+            PowerUp::Berserk => SpriteType::PickupBerserk,
+            PowerUp::Invulnerability => SpriteType::PickupInvulnerability,
+            PowerUp::Invisibility => SpriteType::PickupInvisibility,
+            PowerUp::QuadDamage => SpriteType::PickupQuadDamage,
+        }
+    }
+
+    // Name on the HUD timer
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerUp::Berserk => "BERSERK",
+            PowerUp::Invulnerability => "INVULN",
+            PowerUp::Invisibility => "INVIS",
+            PowerUp::QuadDamage => "QUAD",
+        }
+    }
+
+    // Color the view is tinted with while the effect runs, and how strongly
+    pub fn tint(self) -> ((u8, u8, u8), f64) {
+        match self {
+            PowerUp::Berserk => ((255, 0, 0), 0.15),
+            PowerUp::Invulnerability => ((230, 230, 230), 0.3),
+            PowerUp::Invisibility => ((40, 40, 70), 0.3),
+            PowerUp::QuadDamage => ((130, 60, 255), 0.2),
+        }
+    }
+}
+
+// A power-up running on an entity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Effect {
+    pub power_up: PowerUp,
+    pub remaining: f64,
+}
+
+impl Effect {
+    // Blinking at the end of the effect, like the Doom palette flashes
+    pub fn blink_off(&self) -> bool {
+        self.remaining < RUNNING_OUT && (self.remaining * 4.0) as i32 % 2 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::entity::Entity;
+    use crate::common::pickups::PickupKind;
+
+    #[test]
+    fn invulnerability_wears_off() {
+        let mut player = Entity::new_player(1, 1.5, 1.5);
+        assert!(player.pick_up(PickupKind::PowerUp(PowerUp::Invulnerability)));
+        player.take_damage(50);
+        assert_eq!(player.health, 100);
+
+        // Taking it again starts it over
+        player.update_effects(PowerUp::Invulnerability.duration() - 1.0);
+        player.give_effect(PowerUp::Invulnerability);
+        player.update_effects(2.0);
+        assert!(player.has_effect(PowerUp::Invulnerability));
+
+        player.update_effects(PowerUp::Invulnerability.duration());
+        assert!(player.effects.is_empty());
+        player.take_damage(50);
+        assert_eq!(player.health, 50);
+    }
+}
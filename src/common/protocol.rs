@@ -1,5 +1,8 @@
 use serde::{Serialize, Deserialize};
 use crate::common::world::World;
+use crate::common::events::GameEvent;
+use crate::common::pickups::PickupKind;
+use crate::weapon::AmmoType;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ClientMessage {
@@ -9,8 +12,11 @@ pub enum ClientMessage {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ServerMessage {
+    // Sent once on connection, id of the entity controlled by the client and
+    // the hash of the host's data files
+    Welcome { player_id: u32, data_hash: u64 },
     WorldSnapshot(World),
-    // Events
+    Event(GameEvent),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -23,5 +29,29 @@ pub struct PlayerInput {
     pub rotate_right: bool,
     pub shoot: bool,
     pub reload: bool,
+    pub respawn: bool,
     pub view_angle: f64,
+    pub inventory: Inventory,
+}
+
+// What a client carries, ammo and weapons only live on its side. The host
+// leaves the items the client couldn't take
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Inventory {
+    // Ammo types already at their maximum
+    pub full_ammo: Vec<AmmoType>,
+    // Ids of the weapons owned
+    pub weapons: Vec<String>,
+}
+
+impl Inventory {
+    // Whether Player::give would take the item, see player.rs
+    pub fn wants(&self, kind: PickupKind) -> bool {
+        if let Some((ammo_type, _)) = kind.ammo() {
+            return !self.full_ammo.contains(&ammo_type);
+        }
+        let PickupKind::Weapon(weapon) = kind else { return false };
+        let Some(def) = crate::weapon::definitions().get(weapon) else { return false };
+        !self.weapons.contains(&def.id) || !self.full_ammo.contains(&def.ammo_type)
+    }
 }
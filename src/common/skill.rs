@@ -0,0 +1,100 @@
+/* Difficulty, from 1 (easiest) to 5 (nightmare) like Doom's skill levels.
+It is part of the world so clients, recordings and anything saving the world
+play at the skill the game was started with. */
+
+use serde::{Serialize, Deserialize};
+
+// Skill flags of level things : which skills a thing appears on
+pub const SKILL_EASY: u8 = 1;   // Skills 1 and 2
+pub const SKILL_MEDIUM: u8 = 2; // Skill 3
+pub const SKILL_HARD: u8 = 4;   // Skills 4 and 5
+pub const SKILL_ALL: u8 = SKILL_EASY | SKILL_MEDIUM | SKILL_HARD;
+
+// Seconds before a dead monster gets back up on nightmare
+pub const MONSTER_RESPAWN_TIME: f64 = 12.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Skill(u8);
+
+impl Default for Skill {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+impl Skill {
+    pub const MIN: u8 = 1;
+    pub const MAX: u8 = 5;
+
+    pub fn new(level: u8) -> Self {
+        Self(level.clamp(Self::MIN, Self::MAX))
+    }
+
+    pub fn level(self) -> u8 {
+        self.0
+    }
+
+    // Index in the tables below
+    fn index(self) -> usize {
+        (self.0.clamp(Self::MIN, Self::MAX) - Self::MIN) as usize
+    }
+
+    // Flag a level thing needs to appear at this skill
+    pub fn thing_flag(self) -> u8 {
+        match self.0 {
+            0..=2 => SKILL_EASY,
+            3 => SKILL_MEDIUM,
+            _ => SKILL_HARD,
+        }
+    }
+
+    // Multiplier of the damage monsters deal
+    pub fn damage_scale(self) -> f64 {
+        [0.5, 0.75, 1.0, 1.25, 1.5][self.index()]
+    }
+
+    // Multiplier of the time monsters take to react to a player
+    pub fn reaction_scale(self) -> f64 {
+        [1.6, 1.3, 1.0, 0.8, 0.6][self.index()]
+    }
+
+    // Multiplier of the speed of monster projectiles
+    pub fn projectile_speed_scale(self) -> f64 {
+        [0.8, 0.9, 1.0, 1.15, 1.3][self.index()]
+    }
+
+    // Nightmare : monsters move and attack faster...
+    pub fn fast_monsters(self) -> bool {
+        self.0 >= Self::MAX
+    }
+
+    // ...and get back up a while after dying
+    pub fn respawn_monsters(self) -> bool {
+        self.0 >= Self::MAX
+    }
+
+    // Multipliers of monster speed and attack cooldown
+    pub fn monster_speed_scale(self) -> f64 {
+        if self.fast_monsters() { 1.5 } else { 1.0 }
+    }
+
+    pub fn cooldown_scale(self) -> f64 {
+        if self.fast_monsters() { 0.5 } else { 1.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skills_are_clamped_and_map_to_thing_flags() {
+        assert_eq!(Skill::new(0).level(), 1);
+        assert_eq!(Skill::new(9).level(), 5);
+        assert_eq!(Skill::new(2).thing_flag(), SKILL_EASY);
+        assert_eq!(Skill::default().thing_flag(), SKILL_MEDIUM);
+        assert_eq!(Skill::new(4).thing_flag(), SKILL_HARD);
+        assert!(!Skill::new(4).respawn_monsters() && Skill::new(5).respawn_monsters());
+    }
+}
@@ -0,0 +1,76 @@
+/* Sound propagation : a noise spreads over the empty cells of the level from
+where it was made, so walls and closed gates stop it and only monsters in
+connected areas hear it. Used by the AI to wake monsters on gunfire. */
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::common::level::Level;
+use crate::common::pathfinding::{cell_center, cell_of, Cell};
+
+// Cells a noise travels before dying out
+pub const NOISE_RANGE: u32 = 24;
+
+const STEPS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+fn open(level: &Level, cell: Cell) -> bool {
+    let (x, y) = cell_center(cell);
+    level.is_empty(x, y)
+}
+
+// Cell a noise made at (x, y) starts from. A noise made against a wall (a gun
+// barrel poking into it) starts from an open cell next to it
+pub fn noise_origin(level: &Level, x: f64, y: f64) -> Option<Cell> {
+    let cell = cell_of(x, y);
+    std::iter::once((0, 0)).chain(STEPS)
+        .map(|(dx, dy)| (cell.0 + dx, cell.1 + dy))
+        .find(|&c| open(level, c))
+}
+
+// Cells that hear a noise starting from `origin`, at most `range` steps away
+pub fn noise_area(level: &Level, origin: Cell, range: u32) -> HashSet<Cell> {
+    let mut heard = HashSet::new();
+    let mut open_cells = VecDeque::new();
+    if open(level, origin) {
+        heard.insert(origin);
+        open_cells.push_back((origin, 0));
+    }
+
+    // Breadth first, 4-connected so sound doesn't leak through wall corners
+    while let Some(((cx, cy), distance)) = open_cells.pop_front() {
+        if distance >= range {
+            continue;
+        }
+        for (dx, dy) in STEPS {
+            let next = (cx + dx, cy + dy);
+            if open(level, next) && heard.insert(next) {
+                open_cells.push_back((next, distance + 1));
+            }
+        }
+    }
+    heard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_stopped_by_closed_gates_and_range() {
+        let mut level = Level::debug_1().unwrap();
+        level.set_gates_open(false);
+        // The exit room is only reachable through its gate
+        let exit = cell_of(21.5, 20.5);
+        let origin = noise_origin(&level, 3.5, 3.5).unwrap();
+        let closed = noise_area(&level, origin, 1000);
+        assert!(closed.contains(&cell_of(4.5, 3.5)));
+        assert!(!closed.contains(&exit));
+
+        level.set_gates_open(true);
+        assert!(noise_area(&level, origin, 1000).contains(&exit));
+
+        let near = noise_area(&level, origin, 1);
+        assert!(near.len() <= 5 && near.contains(&origin));
+        // Fired point blank into the outer wall
+        assert_eq!(noise_origin(&level, 0.5, 3.5), Some(cell_of(1.5, 3.5)));
+    }
+}
@@ -0,0 +1,182 @@
+/* Survival waves : monsters come in from the level's spawn points, more of
+them and tougher kinds every wave, with a boss every few waves. A break
+between waves lets the players restock. Runs where the world is simulated. */
+
+use std::collections::HashMap;
+
+use crate::common::ai::AiState;
+use crate::common::enemies;
+use crate::common::entity::EntityType;
+use crate::common::level::Level;
+use crate::common::world::World;
+
+// Seconds between two waves
+pub const BREAK_TIME: f64 = 10.0;
+// Seconds between two monsters of a wave coming in
+const SPAWN_INTERVAL: f64 = 0.8;
+// Monsters don't come in at a spawn point this close to a player
+const SPAWN_CLEARANCE: f64 = 4.0;
+const BOSS_EVERY: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WavePhase {
+    Break,    // Waiting for the next wave
+    Fighting, // Monsters coming in or still alive
+}
+
+#[derive(Debug, Clone)]
+pub struct Waves {
+    // Current wave, 0 before the first one
+    pub wave: u32,
+    pub phase: WavePhase,
+    // Break time left, or time before the next monster comes in
+    pub timer: f64,
+    pub score: u32,
+    // Enemy definitions still to come in this wave, the last one first
+    pending: Vec<usize>,
+    next_spawn_point: usize,
+    // Monsters in the world and the score they are worth once dead
+    alive: HashMap<u32, u32>,
+}
+
+impl Waves {
+    pub fn new() -> Self {
+        Self {
+            wave: 0,
+            phase: WavePhase::Break,
+            timer: BREAK_TIME,
+            score: 0,
+            pending: Vec::new(),
+            next_spawn_point: 0,
+            alive: HashMap::new(),
+        }
+    }
+
+    // Monsters of a wave, weakest kinds first : two more every wave and one
+    // more kind every other wave, plus a boss every BOSS_EVERY waves
+    pub fn composition(wave: u32) -> Vec<usize> {
+        let defs = enemies::definitions();
+        let mut kinds: Vec<usize> = (0..defs.len()).filter(|&i| !defs[i].boss).collect();
+        kinds.sort_by_key(|&i| defs[i].health);
+        if kinds.is_empty() {
+            return Vec::new();
+        }
+        let unlocked = (wave as usize).div_ceil(2).clamp(1, kinds.len());
+
+        let count = 2 + 2 * wave as usize;
+        let mut monsters: Vec<usize> = (0..count).map(|i| kinds[i % unlocked]).collect();
+        if wave.is_multiple_of(BOSS_EVERY) {
+            monsters.extend((0..defs.len()).find(|&i| defs[i].boss));
+        }
+        monsters
+    }
+
+    // Run the waves for one frame. `skip_break` starts the next wave right away.
+    // Returns true when a wave was just cleared, so the modes can restock items
+    pub fn update(&mut self, world: &mut World, level: &Level, skip_break: bool, delta_time: f64) -> bool {
+        // Monsters that left the world since the last frame died
+        let mut scored = 0;
+        self.alive.retain(|id, score| {
+            let present = world.entities.contains_key(id);
+            if !present {
+                scored += *score;
+            }
+            present
+        });
+        self.score += scored;
+        // Summoned and respawned monsters count too
+        for enemy in world.entities.values().filter(|e| e.entity_type == EntityType::Enemy) {
+            self.alive.entry(enemy.id).or_insert_with(|| enemy.definition().map_or(0, |def| def.score));
+        }
+
+        self.timer -= delta_time;
+        match self.phase {
+            WavePhase::Break => {
+                if self.timer <= 0.0 || skip_break {
+                    self.wave += 1;
+                    self.pending = Self::composition(self.wave);
+                    self.pending.reverse();
+                    self.phase = WavePhase::Fighting;
+                    self.timer = 0.0;
+                }
+                false
+            }
+            WavePhase::Fighting => {
+                if self.timer <= 0.0 && !self.pending.is_empty() && self.spawn_next(world, level) {
+                    self.timer = SPAWN_INTERVAL;
+                }
+                let cleared = self.pending.is_empty() && self.alive.is_empty();
+                if cleared {
+                    self.phase = WavePhase::Break;
+                    self.timer = BREAK_TIME;
+                }
+                cleared
+            }
+        }
+    }
+
+    // Bring the next monster in at the next spawn point away from the players,
+    // already hunting. false if every spawn point is too close to a player
+    fn spawn_next(&mut self, world: &mut World, level: &Level) -> bool {
+        let players: Vec<(f64, f64)> = world.get_players().iter()
+            .map(|p| (p.transform.x, p.transform.y))
+            .collect();
+        let count = level.spawn_points.len();
+        let free = (0..count)
+            .map(|i| (self.next_spawn_point + i) % count)
+            .find(|&i| {
+                let (x, y) = level.spawn_points[i];
+                players.iter().all(|p| (p.0 - x).hypot(p.1 - y) >= SPAWN_CLEARANCE)
+            });
+        let (Some(point), Some(enemy_def)) = (free, self.pending.pop()) else { return false };
+
+        self.next_spawn_point = (point + 1) % count;
+        let (x, y) = level.spawn_points[point];
+        let id = world.spawn_enemy_def(x, y, enemy_def);
+        if let Some(enemy) = world.get_entity_mut(id) {
+            enemy.ai_state = AiState::Chase;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waves_grow_and_bring_a_boss() {
+        let defs = enemies::definitions();
+        let first = Waves::composition(1);
+        assert_eq!(first.len(), 4);
+        assert!(first.iter().all(|&i| i == first[0]));
+
+        let fifth = Waves::composition(BOSS_EVERY);
+        assert_eq!(fifth.iter().filter(|&&i| defs[i].boss).count(), 1);
+        assert!(fifth.len() > Waves::composition(BOSS_EVERY - 1).len());
+    }
+
+    #[test]
+    fn clearing_a_wave_scores_and_starts_a_break() {
+        let mut level = Level::debug_1().unwrap();
+        level.spawn_points = vec![(12.5, 12.5)];
+        let mut world = World::new();
+        let mut waves = Waves::new();
+
+        waves.update(&mut world, &level, true, 0.0);
+        assert_eq!((waves.wave, waves.phase), (1, WavePhase::Fighting));
+        // Bring the whole wave in, then kill it
+        for _ in 0..10 {
+            waves.update(&mut world, &level, false, SPAWN_INTERVAL);
+        }
+        let monsters: Vec<u32> = world.entities.keys().copied().collect();
+        assert_eq!(monsters.len(), Waves::composition(1).len());
+        let worth: u32 = world.entities.values().filter_map(|e| e.definition()).map(|def| def.score).sum();
+        for id in monsters {
+            world.remove_entity(id);
+        }
+
+        assert!(waves.update(&mut world, &level, false, 0.0));
+        assert_eq!((waves.phase, waves.score), (WavePhase::Break, worth));
+    }
+}
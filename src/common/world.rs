@@ -1,13 +1,63 @@
 use std::io::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
 use crate::entity::{Entity, EntityType, Transform, SpriteType};
-use crate::level::Level;
+use crate::level::{Level, ThingKind};
+use crate::common::enemies;
+use crate::common::events::GameEvent;
+use crate::common::pickups::{PickupKind, PICKUP_RESPAWN_TIME};
+use crate::common::skill::{Skill, MONSTER_RESPAWN_TIME};
+
+// An item waiting to come back where it was taken
+#[derive(Clone, Debug)]
+struct PickupRespawn {
+    time_left: f64,
+    x: f64,
+    y: f64,
+    kind: PickupKind,
+}
+
+// A dead monster waiting to get back up, on nightmare
+#[derive(Clone, Debug)]
+struct EnemyRespawn {
+    time_left: f64,
+    x: f64,
+    y: f64,
+    enemy_def: usize,
+    // Its corpse, gone once it is back up
+    corpse: Option<u32>,
+}
+
+// Corpses kept when no limit is set, the oldest ones go first
+pub const DEFAULT_CORPSE_LIMIT: usize = 32;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct World {
     pub entities: HashMap<u32, Entity>,
     next_entity_id: u32,
+    // Set once every boss of the level is dead, modes open the level's gates
+    pub gates_open: bool,
+    // Difficulty the game is played at, set by the modes before spawning the level
+    pub skill: Skill,
+    // Most corpses lying around at once, 0 removes monsters as they die
+    pub corpse_limit: usize,
+    // Events raised since the last drain, never sent as part of a snapshot
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+    // Taken items come back after a while, multiplayer modes turn this on
+    #[serde(skip)]
+    pub respawn_pickups: bool,
+    #[serde(skip)]
+    pickup_respawns: Vec<PickupRespawn>,
+    // Dead monsters get back up if the skill wants it, modes with their own
+    // monster supply (survival) turn this off
+    #[serde(skip)]
+    pub respawn_monsters: bool,
+    #[serde(skip)]
+    enemy_respawns: Vec<EnemyRespawn>,
+    // Items dropped by monsters, they don't come back once taken
+    #[serde(skip)]
+    dropped_items: HashSet<u32>,
 }
 
 impl World {
@@ -15,9 +65,31 @@ impl World {
         Self {
             entities: HashMap::new(),
             next_entity_id: 1,
+            gates_open: false,
+            skill: Skill::default(),
+            corpse_limit: DEFAULT_CORPSE_LIMIT,
+            events: Vec::new(),
+            respawn_pickups: false,
+            pickup_respawns: Vec::new(),
+            respawn_monsters: true,
+            enemy_respawns: Vec::new(),
+            dropped_items: HashSet::new(),
         }
     }
 
+    pub fn push_event(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    // Events raised so far this frame, before the mode drains them
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
     pub fn spawn_entity(&mut self, mut entity: Entity) -> u32 {
         let id = self.next_entity_id;
         entity.id = id;
@@ -46,9 +118,13 @@ impl World {
         self.entities.values_mut().find(|e| e.entity_type == EntityType::Player)
     }
 
-    // spawn a projectile at a given position
-    pub fn spawn_projectile(&mut self, x: f64, y: f64, angle: f64, damage: i32, max_distance: f64, sprite_type: SpriteType) -> u32 {
-        let projectile = Entity::new_projectile(0, x, y, angle, damage, max_distance, sprite_type);
+    // spawn a projectile fired by owner_id, it belongs to the owner's team
+    pub fn spawn_projectile(&mut self, owner_id: u32, origin: Transform, damage: i32, max_distance: f64, sprite_type: SpriteType) -> u32 {
+        let mut projectile = Entity::new_projectile(0, origin.x, origin.y, origin.angle, damage, max_distance, sprite_type);
+        projectile.owner_id = Some(owner_id);
+        if let Some(owner) = self.entities.get(&owner_id) {
+            projectile.team = owner.team;
+        }
         self.spawn_entity(projectile)
     }
 
@@ -64,30 +140,182 @@ impl World {
             .collect()
     }
 
-    pub fn spawn_enemy(&mut self, x: f64, y: f64, sprite_type: SpriteType) -> u32 {
-        let enemy = Entity::new_enemy(0, x, y, sprite_type);
+    pub fn get_pickups(&self) -> Vec<&Entity> {
+        self.entities.values()
+            .filter(|e| e.entity_type == EntityType::Pickup && e.active)
+            .collect()
+    }
+
+    pub fn get_corpses(&self) -> Vec<&Entity> {
+        self.entities.values()
+            .filter(|e| e.entity_type == EntityType::Corpse)
+            .collect()
+    }
+
+    pub fn get_players(&self) -> Vec<&Entity> {
+        self.entities.values()
+            .filter(|e| e.entity_type == EntityType::Player && e.active)
+            .collect()
+    }
+
+    // Bring a player entity back to life at a spawn point
+    // Cells under the bodies of players and monsters, gates can't close on them
+    pub fn occupied_cells(&self) -> HashSet<(usize, usize)> {
+        let mut cells = HashSet::new();
+        for entity in self.entities.values() {
+            if !entity.active || !matches!(entity.entity_type, EntityType::Player | EntityType::Enemy) {
+                continue;
+            }
+            let radius = crate::common::physics::Physics::body_radius(entity);
+            let (x, y) = (entity.transform.x, entity.transform.y);
+            for (cx, cy) in [(x - radius, y - radius), (x + radius, y - radius), (x - radius, y + radius), (x + radius, y + radius)] {
+                if cx >= 0.0 && cy >= 0.0 {
+                    cells.insert((cx as usize, cy as usize));
+                }
+            }
+        }
+        cells
+    }
+
+    pub fn respawn_player(&mut self, id: u32, x: f64, y: f64) {
+        if let Some(entity) = self.entities.get_mut(&id) {
+            let fresh = Entity::new_player(id, x, y);
+            *entity = fresh;
+        }
+    }
+
+    // Spawn a monster by its id in data/enemies.toml, None if there is no such enemy
+    pub fn spawn_enemy(&mut self, x: f64, y: f64, id: &str) -> Option<u32> {
+        let index = enemies::index_of(id)?;
+        Some(self.spawn_enemy_def(x, y, index))
+    }
+
+    pub fn spawn_enemy_def(&mut self, x: f64, y: f64, enemy_def: usize) -> u32 {
+        let enemy = Entity::new_enemy(0, x, y, enemy_def);
         self.spawn_entity(enemy)
     }
 
-    pub fn respawn_enemies(&mut self) {
-        // Remove only enemies and projectiles, keep the player
-        let ids_to_remove: Vec<u32> = self.entities.iter()
-            .filter(|(_, e)| e.entity_type == EntityType::Enemy || e.entity_type == EntityType::Projectile)
-            .map(|(id, _)| *id)
-            .collect();
+    pub fn spawn_pickup(&mut self, x: f64, y: f64, kind: PickupKind) -> u32 {
+        let pickup = Entity::new_pickup(0, x, y, kind);
+        self.spawn_entity(pickup)
+    }
+
+    // Item left by a dead monster
+    pub fn spawn_drop(&mut self, x: f64, y: f64, kind: PickupKind) -> u32 {
+        let id = self.spawn_pickup(x, y, kind);
+        self.dropped_items.insert(id);
+        id
+    }
+
+    // Leave the corpse of a monster, making room by removing the oldest ones
+    // past the limit. None when corpses are turned off
+    pub fn spawn_corpse(&mut self, enemy: &Entity) -> Option<u32> {
+        if self.corpse_limit == 0 {
+            return None;
+        }
+        let id = self.spawn_entity(Entity::new_corpse(0, enemy));
+        // Ids only grow, the smallest are the oldest
+        let mut corpses: Vec<u32> = self.get_corpses().iter().map(|e| e.id).collect();
+        if corpses.len() > self.corpse_limit {
+            corpses.sort_unstable();
+            for old in &corpses[..corpses.len() - self.corpse_limit] {
+                self.entities.remove(old);
+            }
+        }
+        Some(id)
+    }
+
+    // Remove a taken item, it comes back later if pickups respawn
+    pub fn take_pickup(&mut self, id: u32) -> Option<PickupKind> {
+        let pickup = self.entities.remove(&id)?;
+        let kind = pickup.pickup?;
+        let dropped = self.dropped_items.remove(&id);
+        if self.respawn_pickups && !dropped {
+            self.pickup_respawns.push(PickupRespawn {
+                time_left: PICKUP_RESPAWN_TIME,
+                x: pickup.transform.x,
+                y: pickup.transform.y,
+                kind,
+            });
+        }
+        Some(kind)
+    }
+
+    // Queue a dead monster to get back up where it fell, if the skill wants it
+    pub fn queue_enemy_respawn(&mut self, x: f64, y: f64, enemy_def: usize, corpse: Option<u32>) {
+        if self.respawn_monsters && self.skill.respawn_monsters() {
+            self.enemy_respawns.push(EnemyRespawn { time_left: MONSTER_RESPAWN_TIME, x, y, enemy_def, corpse });
+        }
+    }
+
+    pub fn update_respawns(&mut self, delta_time: f64) {
+        for respawn in self.pickup_respawns.iter_mut() {
+            respawn.time_left -= delta_time;
+        }
+        let (ready, waiting) = std::mem::take(&mut self.pickup_respawns)
+            .into_iter()
+            .partition(|r| r.time_left <= 0.0);
+        self.pickup_respawns = waiting;
+        for respawn in ready {
+            self.spawn_pickup(respawn.x, respawn.y, respawn.kind);
+        }
 
-        for id in ids_to_remove {
-            self.entities.remove(&id);
+        for respawn in self.enemy_respawns.iter_mut() {
+            respawn.time_left -= delta_time;
         }
+        let (ready, waiting) = std::mem::take(&mut self.enemy_respawns)
+            .into_iter()
+            .partition(|r| r.time_left <= 0.0);
+        self.enemy_respawns = waiting;
+        for respawn in ready {
+            if let Some(corpse) = respawn.corpse {
+                self.entities.remove(&corpse);
+            }
+            self.spawn_enemy_def(respawn.x, respawn.y, respawn.enemy_def);
+        }
+    }
 
-        // Spawn default enemies
-        self.spawn_enemy(10.0, 15.0, SpriteType::EnemyImp);
-        self.spawn_enemy(8.0, 8.0, SpriteType::EnemyDemon);
-        self.spawn_enemy(18.0, 12.0, SpriteType::EnemyImp);
+    // Spawn the monsters and items the level starts with at the world's skill
+    pub fn spawn_things(&mut self, level: &Level) {
+        let flag = self.skill.thing_flag();
+        for thing in level.things.iter().filter(|t| t.skills & flag != 0) {
+            match thing.kind {
+                ThingKind::Enemy(enemy_def) => { self.spawn_enemy_def(thing.x, thing.y, enemy_def); }
+                ThingKind::Pickup(kind) => { self.spawn_pickup(thing.x, thing.y, kind); }
+            }
+        }
     }
 
-    pub fn reset(&mut self) {
+    // Put every item of the level back, without its monsters
+    pub fn restock_pickups(&mut self, level: &Level) {
+        self.entities.retain(|_, e| e.entity_type != EntityType::Pickup);
+        self.pickup_respawns.clear();
+        self.dropped_items.clear();
+        let flag = self.skill.thing_flag();
+        for thing in level.things.iter().filter(|t| t.skills & flag != 0) {
+            if let ThingKind::Pickup(kind) = thing.kind {
+                self.spawn_pickup(thing.x, thing.y, kind);
+            }
+        }
+    }
+
+    // Put the level back as it started, players are kept
+    pub fn restart(&mut self, level: &Level) {
+        self.clear_level();
+        self.spawn_things(level);
+    }
+
+    // Everything but the players gone, gates closed
+    pub fn clear_level(&mut self) {
+        self.entities.retain(|_, e| e.entity_type == EntityType::Player);
+        self.pickup_respawns.clear();
+        self.enemy_respawns.clear();
+        self.dropped_items.clear();
+        self.gates_open = false;
+    }
+
+    pub fn reset(&mut self, level: &Level) {
         self.entities.clear();
-        self.respawn_enemies();
+        self.restart(level);
     }
-}
\ No newline at end of file
+}
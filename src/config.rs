@@ -0,0 +1,74 @@
+/* User settings, read from rusty_doom.toml in the working directory if it
+exists and overridden by command line arguments. */
+
+use std::io::{Error, ErrorKind, Result};
+use serde::Deserialize;
+
+use crate::common::skill::Skill;
+
+pub const CONFIG_PATH: &str = "rusty_doom.toml";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Horizontal field of view in degrees
+    pub fov: f64,
+    // Width / height ratio of a terminal character cell, most fonts are about twice as tall as wide
+    pub cell_aspect: f64,
+    // Difficulty from 1 to 5, see skill.rs
+    pub skill: Skill,
+    // Overrides the level's limit on dead monsters left lying around
+    pub corpse_limit: Option<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fov: 60.0,
+            cell_aspect: 0.5,
+            skill: Skill::default(),
+            corpse_limit: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(args: &[String]) -> Result<Self> {
+        let mut config = match std::fs::read_to_string(CONFIG_PATH) {
+            Ok(text) => toml::from_str(&text)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", CONFIG_PATH, e)))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(e),
+        };
+
+        if let Some(fov) = parse_arg(args, "--fov")? {
+            config.fov = fov;
+        }
+        if let Some(cell_aspect) = parse_arg(args, "--cell-aspect")? {
+            config.cell_aspect = cell_aspect;
+        }
+
+        if let Some(skill) = parse_arg::<u8>(args, "--skill")? {
+            config.skill = Skill::new(skill);
+        }
+        if let Some(corpse_limit) = parse_arg(args, "--corpse-limit")? {
+            config.corpse_limit = Some(corpse_limit);
+        }
+
+        config.fov = config.fov.clamp(30.0, 150.0);
+        config.skill = Skill::new(config.skill.level());
+        config.cell_aspect = config.cell_aspect.clamp(0.1, 2.0);
+        Ok(config)
+    }
+}
+
+// Value following `name` in the arguments, if present
+pub fn parse_arg<T: std::str::FromStr>(args: &[String], name: &str) -> Result<Option<T>> {
+    match args.iter().position(|a| a == name) {
+        None => Ok(None),
+        Some(i) => args.get(i + 1)
+            .and_then(|v| v.parse().ok())
+            .map(Some)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} expects a value", name))),
+    }
+}
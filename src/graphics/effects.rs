@@ -0,0 +1,165 @@
+/* Screen effects applied around the 3D view : damage and pickup flashes,
+power-up tints, camera shake and weapon bob. The effects keep their own
+clock and only look at the local player, so every game mode drives them
+the same way. */
+
+use std::time::Instant;
+use crossterm::style::Color;
+use rand::Rng;
+
+use crate::common::powerups::Effect;
+use crate::entity::Transform;
+use crate::graphics::{RenderBuffer, Viewport};
+use crate::player::Player;
+
+const FLASH_DECAY: f64 = 2.5; // Flash intensity lost per second
+const SHAKE_DECAY: f64 = 3.0;
+// Explosions shake the camera up to this many blast radii away
+const EXPLOSION_SHAKE_RANGE: f64 = 3.0;
+const BOB_SPEED: f64 = 10.0; // Radians per second while moving
+const BOB_RAMP: f64 = 5.0; // How fast the bob fades in and out
+
+pub struct ScreenEffects {
+    last_update: Instant,
+    damage_flash: f64,
+    pickup_flash: f64,
+    shake: f64,
+    bob_phase: f64,
+    bob_amount: f64,
+    last_health: u32,
+    last_armor: u32,
+    last_position: (f64, f64),
+    dead: bool,
+    power_ups: Vec<Effect>,
+}
+
+impl ScreenEffects {
+    pub fn new() -> Self {
+        Self {
+            last_update: Instant::now(),
+            damage_flash: 0.0,
+            pickup_flash: 0.0,
+            shake: 0.0,
+            bob_phase: 0.0,
+            bob_amount: 0.0,
+            last_health: 0,
+            last_armor: 0,
+            last_position: (0.0, 0.0),
+            dead: false,
+            power_ups: Vec::new(),
+        }
+    }
+
+    pub fn trigger_damage(&mut self, amount: u32) {
+        self.damage_flash = (self.damage_flash + amount as f64 / 25.0).min(1.0);
+        self.trigger_shake(amount as f64 / 50.0);
+    }
+
+    pub fn trigger_pickup(&mut self) {
+        self.pickup_flash = 0.6;
+    }
+
+    pub fn trigger_shake(&mut self, intensity: f64) {
+        self.shake = (self.shake + intensity).min(1.0);
+    }
+
+    // Shake for an explosion, stronger the closer the camera is to it
+    pub fn trigger_explosion(&mut self, (x, y): (f64, f64), radius: f64, camera: &Transform) {
+        let distance = (x - camera.x).hypot(y - camera.y);
+        let closeness = 1.0 - distance / (radius * EXPLOSION_SHAKE_RANGE);
+        if closeness > 0.0 {
+            self.trigger_shake(0.8 * closeness);
+        }
+    }
+
+    // Advance the effects and react to changes of the local player
+    pub fn update(&mut self, player: &Player) {
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.last_update).as_secs_f64();
+        self.last_update = now;
+
+        // Hits soaked up by the armor still hurt
+        let lost = self.last_health.saturating_sub(player.health) + self.last_armor.saturating_sub(player.armor);
+        if lost > 0 {
+            self.trigger_damage(lost);
+        }
+        self.last_health = player.health;
+        self.last_armor = player.armor;
+        self.dead = player.is_dead();
+        self.power_ups.clone_from(&player.effects);
+
+        let position = (player.transform.x, player.transform.y);
+        let moved = (position.0 - self.last_position.0).abs() + (position.1 - self.last_position.1).abs();
+        self.last_position = position;
+
+        // Only bob for small steps, a teleport or respawn should not count
+        if moved > 1e-4 && moved < 1.0 {
+            self.bob_phase += BOB_SPEED * delta_time;
+            self.bob_amount = (self.bob_amount + BOB_RAMP * delta_time).min(1.0);
+        } else {
+            self.bob_amount = (self.bob_amount - BOB_RAMP * delta_time).max(0.0);
+        }
+
+        self.damage_flash = (self.damage_flash - FLASH_DECAY * delta_time).max(0.0);
+        self.pickup_flash = (self.pickup_flash - FLASH_DECAY * delta_time).max(0.0);
+        self.shake = (self.shake - SHAKE_DECAY * delta_time).max(0.0);
+    }
+
+    // Camera used to render the view, jittered while shaking
+    pub fn camera_transform(&self, transform: &Transform) -> Transform {
+        if self.shake <= 0.0 {
+            return *transform;
+        }
+        let mut rng = rand::thread_rng();
+        let offset = 0.05 * self.shake;
+        Transform::new(
+            transform.x + rng.gen_range(-offset..=offset),
+            transform.y + rng.gen_range(-offset..=offset),
+            transform.angle + rng.gen_range(-2.0..=2.0) * self.shake,
+        )
+    }
+
+    // Offset of the weapon sprite in cells (x, y)
+    pub fn weapon_offset(&self) -> (i32, i32) {
+        let x = self.bob_phase.sin() * 3.0 * self.bob_amount;
+        let y = self.bob_phase.cos().abs() * 2.0 * self.bob_amount;
+        (x.round() as i32, y.round() as i32)
+    }
+
+    // Post-process pass tinting the viewport
+    pub fn apply(&self, buffer: &mut RenderBuffer, viewport: &Viewport) {
+        if self.dead {
+            tint(buffer, viewport, (120, 0, 0), 0.5);
+        }
+        for effect in self.power_ups.iter().filter(|e| !e.blink_off()) {
+            let (color, strength) = effect.power_up.tint();
+            tint(buffer, viewport, color, strength);
+        }
+        if self.damage_flash > 0.0 {
+            tint(buffer, viewport, (255, 0, 0), self.damage_flash * 0.6);
+        }
+        if self.pickup_flash > 0.0 {
+            tint(buffer, viewport, (255, 200, 0), self.pickup_flash * 0.4);
+        }
+    }
+}
+
+impl Default for ScreenEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn tint(buffer: &mut RenderBuffer, viewport: &Viewport, (tr, tg, tb): (u8, u8, u8), strength: f64) {
+    let strength = strength.clamp(0.0, 1.0);
+    let blend = |c: u8, t: u8| (c as f64 + (t as f64 - c as f64) * strength) as u8;
+    let rows = viewport.y as usize..(viewport.y + viewport.height) as usize;
+    let columns = viewport.x as usize..(viewport.x + viewport.width) as usize;
+    for row in buffer.buffer.iter_mut().take(rows.end).skip(rows.start) {
+        for cell in row.iter_mut().take(columns.end).skip(columns.start) {
+            if let Color::Rgb { r, g, b } = cell.0 {
+                cell.0 = Color::Rgb { r: blend(r, tr), g: blend(g, tg), b: blend(b, tb) };
+            }
+        }
+    }
+}
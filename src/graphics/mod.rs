@@ -1,4 +1,11 @@
 pub mod rendering;
 pub mod sprites;
+pub mod particles;
+pub mod effects;
+pub mod projection;
+pub mod recording;
 
-pub use self::rendering::{RenderBuffer, draw};
\ No newline at end of file
+pub use self::rendering::{RenderBuffer, draw};
+pub use self::particles::ParticleSystem;
+pub use self::effects::ScreenEffects;
+pub use self::projection::{Projection, Viewport};
\ No newline at end of file
@@ -0,0 +1,150 @@
+/* Lightweight particle system for purely visual effects : blood, sparks,
+smoke and muzzle flashes. Particles live outside of the world entities so
+they are never networked, every peer spawns its own from game events. */
+
+use rand::Rng;
+use crate::common::events::GameEvent;
+use crate::common::level::Level;
+
+pub const MAX_PARTICLES: usize = 512;
+const GRAVITY: f64 = 4.0;
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64, // Height above the floor, 0.5 is eye level
+    pub vx: f64,
+    pub vy: f64,
+    pub vz: f64,
+    pub gravity: f64, // Multiplier, negative values make the particle rise
+    pub life: f64,
+    pub max_life: f64,
+    pub color: (u8, u8, u8),
+    pub size: f64, // World units
+}
+
+// Look of a family of particles
+#[derive(Debug, Clone, Copy)]
+struct ParticleStyle {
+    color: (u8, u8, u8),
+    speed: f64,
+    life: f64,
+    gravity: f64,
+}
+
+const SPARK: ParticleStyle = ParticleStyle { color: (255, 200, 80), speed: 1.5, life: 0.3, gravity: 1.0 };
+const SMOKE: ParticleStyle = ParticleStyle { color: (120, 120, 120), speed: 0.3, life: 0.8, gravity: -0.1 };
+const BLOOD: ParticleStyle = ParticleStyle { color: (180, 0, 0), speed: 1.2, life: 0.6, gravity: 1.0 };
+const GORE: ParticleStyle = ParticleStyle { color: (150, 0, 0), speed: 1.8, life: 1.0, gravity: 1.0 };
+const FLASH: ParticleStyle = ParticleStyle { color: (255, 240, 150), speed: 1.0, life: 0.08, gravity: 0.0 };
+
+#[derive(Default)]
+pub struct ParticleSystem {
+    pub particles: Vec<Particle>,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+        }
+    }
+
+    pub fn spawn(&mut self, particle: Particle) {
+        if self.particles.len() >= MAX_PARTICLES {
+            // Drop the oldest particle to make room
+            self.particles.remove(0);
+        }
+        self.particles.push(particle);
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::WallImpact { x, y, angle } => {
+                // Sparks bounce back from the wall, smoke slowly rises
+                self.burst((x, y, 0.5), angle + 180.0, 120.0, 8, SPARK);
+                self.burst((x, y, 0.5), angle + 180.0, 90.0, 3, SMOKE);
+            }
+            GameEvent::EntityHit { x, y, .. } => {
+                self.burst((x, y, 0.5), 0.0, 360.0, 10, BLOOD);
+            }
+            GameEvent::EntityDeath { x, y, .. } => {
+                self.burst((x, y, 0.5), 0.0, 360.0, 30, GORE);
+                self.burst((x, y, 0.3), 0.0, 360.0, 6, SMOKE);
+            }
+            GameEvent::WeaponFired { x, y, angle } => {
+                self.burst((x, y, 0.45), angle, 30.0, 5, FLASH);
+            }
+            GameEvent::BulletPuff { x, y } => {
+                self.burst((x, y, 0.5), 0.0, 360.0, 4, SPARK);
+                self.burst((x, y, 0.5), 0.0, 360.0, 2, SMOKE);
+            }
+            GameEvent::EnemyAttack { x, y, angle, melee: false, .. } => {
+                self.burst((x, y, 0.5), angle, 40.0, 4, FLASH);
+            }
+            GameEvent::EnemyAttack { .. } => {}
+            GameEvent::Explosion { x, y, radius } => {
+                let count = (radius * 12.0) as usize;
+                self.burst((x, y, 0.5), 0.0, 360.0, count, FLASH);
+                self.burst((x, y, 0.5), 0.0, 360.0, count, SPARK);
+                self.burst((x, y, 0.4), 0.0, 360.0, count / 2, SMOKE);
+            }
+            GameEvent::ItemPickup { .. } | GameEvent::WeaponSound { .. } | GameEvent::EnemyKilled { .. } => {}
+        }
+    }
+
+    // Spawn `count` particles spreading from `origin` around `angle` (degrees)
+    fn burst(&mut self, origin: (f64, f64, f64), angle: f64, spread: f64, count: usize, style: ParticleStyle) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..count {
+            let dir = (angle + rng.gen_range(-spread / 2.0..=spread / 2.0)).to_radians();
+            let speed = style.speed * rng.gen_range(0.3..1.0);
+            let life = style.life * rng.gen_range(0.6..1.0);
+            self.spawn(Particle {
+                x: origin.0,
+                y: origin.1,
+                z: origin.2,
+                vx: dir.cos() * speed,
+                vy: dir.sin() * speed,
+                vz: rng.gen_range(0.0..1.0) * speed,
+                gravity: style.gravity,
+                life,
+                max_life: life,
+                color: style.color,
+                size: 0.05,
+            });
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f64, level: &Level) {
+        for p in self.particles.iter_mut() {
+            p.life -= delta_time;
+            p.vz -= GRAVITY * p.gravity * delta_time;
+
+            let new_x = p.x + p.vx * delta_time;
+            let new_y = p.y + p.vy * delta_time;
+            if level.is_empty(new_x, new_y) {
+                p.x = new_x;
+                p.y = new_y;
+            } else {
+                // Stick to the wall
+                p.vx = 0.0;
+                p.vy = 0.0;
+            }
+
+            p.z += p.vz * delta_time;
+            if p.z < 0.0 {
+                // Settle on the floor with a small bounce
+                p.z = 0.0;
+                p.vz = -p.vz * 0.3;
+                p.vx *= 0.5;
+                p.vy *= 0.5;
+            } else if p.z > 1.0 {
+                p.z = 1.0;
+                p.vz = 0.0;
+            }
+        }
+        self.particles.retain(|p| p.life > 0.0);
+    }
+}
@@ -0,0 +1,95 @@
+/* Perspective projection shared by walls, sprites and particles. It works in
+terminal cells : columns for the horizontal axis and rows for the vertical
+one, using the cell aspect ratio so proportions don't depend on the shape of
+the terminal or its font. */
+
+use crate::config::Config;
+
+// Sub-rectangle of the render buffer a view is drawn into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Viewport {
+    pub fn full(size: (u16, u16)) -> Self {
+        Self { x: 0, y: 0, width: size.0, height: size.1 }
+    }
+
+    // Split the screen between `count` local players : side by side for two,
+    // a 2x2 grid for three or four
+    pub fn split(size: (u16, u16), count: usize) -> Vec<Self> {
+        let (w, h) = size;
+        let (left, right) = (w / 2, w - w / 2);
+        let (top, bottom) = (h / 2, h - h / 2);
+        match count {
+            0 | 1 => vec![Self::full(size)],
+            2 => vec![
+                Self { x: 0, y: 0, width: left, height: h },
+                Self { x: left, y: 0, width: right, height: h },
+            ],
+            _ => vec![
+                Self { x: 0, y: 0, width: left, height: top },
+                Self { x: left, y: 0, width: right, height: top },
+                Self { x: 0, y: top, width: left, height: bottom },
+                Self { x: left, y: top, width: right, height: bottom },
+            ].into_iter().take(count).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Projection {
+    pub viewport: Viewport,
+    pub width: u16,
+    pub height: u16,
+    pub fov: f64,         // Horizontal field of view in degrees
+    pub cell_aspect: f64, // Width / height of a character cell
+    pub focal: f64,       // Distance to the projection plane, in columns
+}
+
+impl Projection {
+    pub fn new(config: &Config, viewport: Viewport) -> Self {
+        let half_fov = (config.fov / 2.0).to_radians();
+        Self {
+            viewport,
+            width: viewport.width,
+            height: viewport.height,
+            fov: config.fov,
+            cell_aspect: config.cell_aspect,
+            focal: (viewport.width as f64 / 2.0) / half_fov.tan(),
+        }
+    }
+
+    // Angle (degrees) between the view direction and the ray through column x
+    pub fn column_angle(&self, x: u16) -> f64 {
+        let offset = x as f64 + 0.5 - self.width as f64 / 2.0;
+        offset.atan2(self.focal).to_degrees()
+    }
+
+    // Screen column of a point seen at relative_angle degrees, None outside the FOV
+    pub fn screen_x(&self, relative_angle: f64) -> Option<f64> {
+        if relative_angle.abs() > self.fov / 2.0 {
+            return None;
+        }
+        Some(self.width as f64 / 2.0 + self.focal * relative_angle.to_radians().tan())
+    }
+
+    // Number of columns covered by one world unit at a perpendicular distance
+    pub fn columns_per_unit(&self, distance: f64) -> f64 {
+        self.focal / distance
+    }
+
+    // Number of rows covered by one world unit at a perpendicular distance
+    pub fn rows_per_unit(&self, distance: f64) -> f64 {
+        self.focal * self.cell_aspect / distance
+    }
+
+    // Row of the horizon (eye level)
+    pub fn horizon(&self) -> f64 {
+        self.height as f64 / 2.0
+    }
+}
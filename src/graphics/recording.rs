@@ -0,0 +1,166 @@
+/* Gameplay recording in the asciinema asciicast v2 format : a JSON header
+line followed by one [time, "o", data] line per frame written by
+RenderBuffer::flush. The files can be replayed with --play or shared and
+played by any asciicast player. */
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crossterm::{
+  cursor::MoveTo, event::{self, Event, KeyCode, KeyModifiers}, queue, style::{Print, ResetColor}, terminal::{Clear, ClearType, SetSize}
+};
+use serde::{Deserialize, Serialize};
+
+use crate::common::skill::Skill;
+
+// Only one recording per process, written by every RenderBuffer
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    // Skill the game was played at, players of other programs ignore it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<u8>,
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+    size: (u16, u16),
+}
+
+impl Recorder {
+    pub fn create(path: &str, size: (u16, u16), skill: Skill) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let header = CastHeader {
+            version: 2,
+            width: size.0,
+            height: size.1,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs()),
+            title: Some("Rusty Doom".to_string()),
+            skill: Some(skill.level()),
+        };
+        writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+        Ok(Self { writer, start: Instant::now(), size })
+    }
+
+    fn write_event(&mut self, code: &str, data: &str) -> Result<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        writeln!(self.writer, "{}", serde_json::to_string(&(time, code, data))?)
+    }
+
+    // Record the bytes of one frame, with a resize event if the screen changed
+    pub fn capture(&mut self, size: (u16, u16), bytes: &[u8]) -> Result<()> {
+        if size != self.size {
+            self.size = size;
+            self.write_event("r", &format!("{}x{}", size.0, size.1))?;
+        }
+        self.write_event("o", &String::from_utf8_lossy(bytes))?;
+        // Flush every frame so a crash still leaves a usable file
+        self.writer.flush()
+    }
+}
+
+// Start recording every frame to `path`
+pub fn start(path: &str, size: (u16, u16), skill: Skill) -> Result<()> {
+    let recorder = Recorder::create(path, size, skill)?;
+    *RECORDER.lock().unwrap() = Some(recorder);
+    Ok(())
+}
+
+// Flush and close the current recording, if any
+pub fn stop() -> Result<()> {
+    if let Some(mut recorder) = RECORDER.lock().unwrap().take() {
+        recorder.writer.flush()?;
+    }
+    Ok(())
+}
+
+// Called by RenderBuffer::flush with the bytes it sent to the terminal
+pub fn capture(size: (u16, u16), bytes: &[u8]) -> Result<()> {
+    match RECORDER.lock().unwrap().as_mut() {
+        Some(recorder) => recorder.capture(size, bytes),
+        None => Ok(()),
+    }
+}
+
+// Replay a recording in the terminal, q, Esc or Ctrl+C stop it
+pub fn play(path: &str) -> Result<()> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let invalid = |e: serde_json::Error| Error::new(ErrorKind::InvalidData, format!("{}: {}", path, e));
+
+    let header: CastHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?).map_err(invalid)?,
+        None => return Err(Error::new(ErrorKind::InvalidData, format!("{}: empty recording", path))),
+    };
+    if header.version != 2 {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{}: unsupported asciicast version {}", path, header.version)));
+    }
+
+    let mut stdout = std::io::stdout();
+    queue!(stdout, Clear(ClearType::All))?;
+    let start = Instant::now();
+
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (time, code, data): (f64, String, String) = serde_json::from_str(&line).map_err(invalid)?;
+
+        // Wait for the frame time while still listening to the keyboard
+        let due = Duration::from_secs_f64(time.max(0.0));
+        while let Some(wait) = due.checked_sub(start.elapsed()) {
+            if wait.is_zero() {
+                break;
+            }
+            if event::poll(wait)? && stop_requested(event::read()?) {
+                return Ok(());
+            }
+        }
+
+        match code.as_str() {
+            "o" => stdout.write_all(data.as_bytes())?,
+            // The game window was resized : follow it where the terminal
+            // allows, and wipe what the old size left on screen
+            "r" => {
+                let size = data.split_once('x').and_then(|(cols, rows)| Some((cols.parse().ok()?, rows.parse().ok()?)));
+                if let Some((cols, rows)) = size {
+                    queue!(stdout, SetSize(cols, rows))?;
+                }
+                queue!(stdout, Clear(ClearType::All))?;
+            }
+            _ => {}
+        }
+        stdout.flush()?;
+    }
+
+    // Keep the last frame on screen until a key is pressed
+    let message = match header.skill {
+        Some(skill) => format!("End of recording (skill {}), press any key", skill),
+        None => "End of recording, press any key".to_string(),
+    };
+    queue!(stdout, ResetColor, MoveTo(0, 0), Print(message))?;
+    stdout.flush()?;
+    loop {
+        if let Event::Key(_) = event::read()? {
+            return Ok(());
+        }
+    }
+}
+
+fn stop_requested(event: Event) -> bool {
+    match event {
+        Event::Key(key) => matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)),
+        _ => false,
+    }
+}
@@ -1,14 +1,23 @@
 use std::io::Result;
-use std::io::Stdout;
+use std::io::{Stdout, Write};
 use crossterm::{
   cursor::MoveTo, execute, queue, style::{Color, Print, SetBackgroundColor}
 };
+use rand::Rng;
 use rayon::prelude::*;
 
 use crate::modes::solo::SoloGame;
-use crate::weapon::WeaponSprite;
-use crate::entity::{Entity, SpriteType, EntityState};
-use crate::graphics::sprites::{get_sprite_frame, Sprite};
+use crate::weapon::{AmmoType, WeaponSprite};
+use crate::entity::{ArmorTier, Entity, SpriteType, EntityState};
+use crate::common::ai::AiState;
+use crate::common::powerups::PowerUp;
+use crate::common::waves::{Waves, WavePhase};
+use crate::highscores::HighScores;
+use crate::graphics::sprites::{get_sprite_frame, get_sprite_size, Sprite};
+use crate::graphics::particles::ParticleSystem;
+use crate::graphics::effects::ScreenEffects;
+use crate::graphics::projection::{Projection, Viewport};
+use crate::graphics::recording;
 
 #[derive(Debug, Clone)]
 pub struct SpriteProjection {
@@ -24,6 +33,8 @@ pub struct SpriteProjection {
     pub sprite_type: SpriteType,
     pub frame: usize,
     pub state: EntityState,
+    // Invisible entities are drawn as a shimmer of what is behind them
+    pub shimmer: bool,
 }
 
 pub struct RenderBuffer {
@@ -69,22 +80,63 @@ impl RenderBuffer {
         }
     }
 
+    // Clear the cells and depth of a viewport only
+    pub fn clear_viewport(&mut self, viewport: &Viewport) {
+        for y in viewport.y..viewport.y + viewport.height {
+            for x in viewport.x..viewport.x + viewport.width {
+                self.set(x, y, Color::Reset, ' ');
+            }
+        }
+        for x in viewport.x..(viewport.x + viewport.width).min(self.width) {
+            self.depth_buffer[x as usize] = f64::MAX;
+        }
+    }
+
+    // Set a cell in viewport coordinates, clipped to the viewport
+    pub fn set_in(&mut self, viewport: &Viewport, x: u16, y: u16, color: Color, ch: char) {
+        if x < viewport.width && y < viewport.height {
+            self.set(viewport.x + x, viewport.y + y, color, ch);
+        }
+    }
+
+    // Color of a cell in viewport coordinates
+    pub fn get_in(&self, viewport: &Viewport, x: u16, y: u16) -> Option<Color> {
+        if x >= viewport.width || y >= viewport.height {
+            return None;
+        }
+        self.buffer.get((viewport.y + y) as usize)?.get((viewport.x + x) as usize).map(|cell| cell.0)
+    }
+
+    pub fn depth_in(&self, viewport: &Viewport, x: u16) -> f64 {
+        self.depth_buffer.get((viewport.x + x) as usize).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_depth_in(&mut self, viewport: &Viewport, x: u16, depth: f64) {
+        if let Some(d) = self.depth_buffer.get_mut((viewport.x + x) as usize) {
+            *d = depth;
+        }
+    }
+
     pub fn flush(&self, stdout: &mut Stdout) -> Result<()> {
-        queue!(stdout, MoveTo(0, 0))?;
+        // Build the frame in memory so it can also be recorded
+        let mut frame: Vec<u8> = Vec::new();
+        queue!(frame, MoveTo(0, 0))?;
         let mut current_color = Color::Reset;
         
         for y in 0..self.height {
-            queue!(stdout, MoveTo(0, y))?;
+            queue!(frame, MoveTo(0, y))?;
             for x in 0..self.width {
                 let (color, ch) = self.buffer[y as usize][x as usize];
                 if color != current_color {
-                    queue!(stdout, SetBackgroundColor(color))?;
+                    queue!(frame, SetBackgroundColor(color))?;
                     current_color = color;
                 }
-                queue!(stdout, Print(ch))?;
+                queue!(frame, Print(ch))?;
             }
         }
+        stdout.write_all(&frame)?;
         execute!(stdout)?;
+        recording::capture((self.width, self.height), &frame)?;
         Ok(())
     }
 }
@@ -94,40 +146,44 @@ use crate::common::world::World;
 
 use crate::common::level::Level;
 
-pub fn draw(world: &World, player: &Player, level: &Level, term_size: (u16, u16), buffer: &mut RenderBuffer) -> Result<()>  {
-  let fov = 60.0_f64;
-  let ray_angle_increment = fov / term_size.0 as f64;
+// Rays stop there, the map is much smaller anyway
+const MAX_VIEW_DISTANCE: f64 = 50.0;
+
+pub fn draw(world: &World, player: &Player, level: &Level, particles: &ParticleSystem, effects: &ScreenEffects, projection: &Projection, buffer: &mut RenderBuffer) -> Result<()>  {
+  let term_size = (projection.width, projection.height);
+  let viewport = projection.viewport;
+  // Camera, possibly shaken by screen effects
+  let view = effects.camera_transform(&player.transform);
   
-  // Resize buffer if needed
-  buffer.resize(term_size.0, term_size.1);
-  buffer.clear(); // Important to clear depth buffer
+  // Only clear our own part of the buffer, other viewports may share it
+  buffer.clear_viewport(&viewport); // Important to clear depth buffer
 
     // 1. CAST RAYS & DRAW WALLS
     // Parallelize the raycasting calculation
     let column_data: Vec<(usize, f64, u8, u16, u16)> = (0..term_size.0)
         .into_par_iter()
         .map(|x| {
-            let angle_offset = (x as f64 - term_size.0 as f64 / 2.0) * ray_angle_increment;
-            let ray_angle = player.transform.angle + angle_offset;
+            let angle_offset = projection.column_angle(x);
+            let ray_angle = view.angle + angle_offset;
 
-            let (wall_distance, wall_type) = cast_wall_ray(
-                player.transform.x, 
-                player.transform.y, 
+            let (ray_distance, wall_type) = level.cast_ray(
+                view.x, 
+                view.y, 
                 ray_angle, 
-                level
+                MAX_VIEW_DISTANCE
             );
+
+            // Distance to the camera plane, the ray length would give a fisheye effect
+            let wall_distance = ray_distance * angle_offset.to_radians().cos();
             
-            // FIXME: Level access. For now using debug_1() as placeholder if we can't access it.
-            // But we should pass it.
-            
-            let wall_height = if wall_distance > 0.1 { 
-                (term_size.1 as f64 * 6.0) / wall_distance
+            let wall_height = if wall_distance > 0.01 { 
+                projection.rows_per_unit(wall_distance)
             } else { 
                 term_size.1 as f64 
             };
 
-            let wall_start = ((term_size.1 as f64 - wall_height) / 2.0).max(0.0) as u16;
-            let wall_end = ((term_size.1 as f64 + wall_height) / 2.0).min(term_size.1 as f64) as u16;
+            let wall_start = (projection.horizon() - wall_height / 2.0).max(0.0) as u16;
+            let wall_end = (projection.horizon() + wall_height / 2.0).min(term_size.1 as f64) as u16;
             
             (x as usize, wall_distance, wall_type, wall_start, wall_end)
         })
@@ -138,9 +194,7 @@ pub fn draw(world: &World, player: &Player, level: &Level, term_size: (u16, u16)
       let x = x as u16;
       
       // Store depth for sprite occlusion
-      if x < buffer.width {
-          buffer.depth_buffer[x as usize] = wall_distance;
-      }
+      buffer.set_depth_in(&viewport, x, wall_distance);
       
       // Draw vertical strip
       for y in 0..term_size.1 {
@@ -151,17 +205,45 @@ pub fn draw(world: &World, player: &Player, level: &Level, term_size: (u16, u16)
           } else {
               get_floor_color(wall_distance) // Floor
           };
-          buffer.set(x, y, color, ' ');
+          buffer.set_in(&viewport, x, y, color, ' ');
       }
     }
 
     // 2. PREPARE SPRITES
     let mut sprite_projections = Vec::new();
     
+    // Project corpses lying around
+    for corpse in world.get_corpses() {
+      if let Some(projection) = project_sprite_to_screen(
+        &view, corpse, projection
+      ) {
+        sprite_projections.push(projection);
+      }
+    }
+
     // Project all enemies
     for enemy in world.get_enemies() {
       if let Some(projection) = project_sprite_to_screen(
-        &player.transform, enemy, term_size.0, term_size.1, fov
+        &view, enemy, projection
+      ) {
+        sprite_projections.push(projection);
+      }
+    }
+    
+    // Project other players
+    for other in world.get_players() {
+      if other.id == player.entity_id { continue; }
+      if let Some(projection) = project_sprite_to_screen(
+        &view, other, projection
+      ) {
+        sprite_projections.push(projection);
+      }
+    }
+    
+    // Project items lying around
+    for pickup in world.get_pickups() {
+      if let Some(projection) = project_sprite_to_screen(
+        &view, pickup, projection
       ) {
         sprite_projections.push(projection);
       }
@@ -170,7 +252,7 @@ pub fn draw(world: &World, player: &Player, level: &Level, term_size: (u16, u16)
     // Project all projectiles
     for projectile in world.get_projectiles() {
       if let Some(projection) = project_sprite_to_screen(
-        &player.transform, projectile, term_size.0, term_size.1, fov
+        &view, projectile, projection
       ) {
         sprite_projections.push(projection);
       }
@@ -180,14 +262,15 @@ pub fn draw(world: &World, player: &Player, level: &Level, term_size: (u16, u16)
     sprite_projections.sort_by(|a, b| b.distance.partial_cmp(&a.distance).unwrap());
 
     // 3. DRAW SPRITES
+    let mut rng = rand::thread_rng();
     for sprite_proj in sprite_projections {
         let sprite = get_sprite_frame(sprite_proj.sprite_type, sprite_proj.frame, sprite_proj.state);
         let brightness = get_distance_brightness(sprite_proj.distance);
         
         for x in sprite_proj.left_column..=sprite_proj.right_column {
-            if x < buffer.width {
+            if x < viewport.width {
                 // Z-Buffer check
-                if sprite_proj.distance < buffer.depth_buffer[x as usize] {
+                if sprite_proj.distance < buffer.depth_in(&viewport, x) {
                     // Calculate U coordinate
                     let u = (x as f64 - (sprite_proj.screen_x - sprite_proj.screen_width / 2.0)) / sprite_proj.screen_width;
                     
@@ -196,42 +279,182 @@ pub fn draw(world: &World, player: &Player, level: &Level, term_size: (u16, u16)
                         let v = (y as f64 - (sprite_proj.screen_y - sprite_proj.screen_height / 2.0)) / sprite_proj.screen_height;
                         
                         if let Some(base_color) = sprite.get_pixel(u, v) {
-                            let color = darken_color_by_brightness(base_color, brightness);
-                            buffer.set(x, y, color, ' ');
+                            let color = if sprite_proj.shimmer {
+                                // Darkened copy of a cell just above or below, like Doom's fuzz
+                                let from = if rng.gen_bool(0.5) { y.saturating_sub(1) } else { y + 1 };
+                                let behind = buffer.get_in(&viewport, x, from).unwrap_or(base_color);
+                                darken_color_by_brightness(behind, if rng.gen_bool(0.5) { 0.5 } else { 0.7 })
+                            } else {
+                                darken_color_by_brightness(base_color, brightness)
+                            };
+                            buffer.set_in(&viewport, x, y, color, ' ');
                         }
                     }
                 }
             }
         }
     }
+
+    // 4. DRAW PARTICLES
+    draw_particles(particles, &view, projection, buffer);
     
-    // Draw weapon sprite overlay in bottom center
-    draw_weapon_sprite(player, term_size, buffer)?;
+    // Draw weapon sprite overlay in bottom center, dropped when dead
+    if !player.is_dead() {
+        draw_weapon_sprite(player, effects.weapon_offset(), &viewport, buffer)?;
+    }
+
+    // Tint the view (damage, pickups) before the HUD so it stays readable
+    effects.apply(buffer, &viewport);
     
     // Draw HUD
-    draw_hud(player, term_size, buffer)?;
+    draw_hud(player, &viewport, buffer)?;
+    draw_boss_bar(world, player, &viewport, buffer);
+
+    if player.is_dead() {
+        draw_death_screen(&viewport, buffer);
+    }
   
   Ok(())
 }
 
-fn draw_hud(player: &Player, term_size: (u16, u16), buffer: &mut RenderBuffer) -> Result<()> {
+fn draw_particles(particles: &ParticleSystem, view: &Transform, projection: &Projection, buffer: &mut RenderBuffer) {
+    for particle in &particles.particles {
+        let (distance, relative_angle) = view_space(view, particle.x, particle.y);
+        if distance < 0.1 { continue; }
+        let screen_x = match projection.screen_x(relative_angle) {
+            Some(screen_x) => screen_x,
+            None => continue,
+        };
+
+        // Same projection as walls and sprites, eye level is at z = 0.5
+        let distance = distance * relative_angle.to_radians().cos();
+        let screen_y = projection.horizon() + (0.5 - particle.z) * projection.rows_per_unit(distance);
+        let size = (particle.size * projection.columns_per_unit(distance)).max(1.0);
+
+        // Fade out at the end of the particle life
+        let fade = (particle.life / particle.max_life * 2.0).min(1.0);
+        let (r, g, b) = particle.color;
+        let color = darken_color(r, g, b, fade * get_distance_brightness(distance));
+
+        let left = (screen_x - size / 2.0).max(0.0) as u16;
+        let top = (screen_y - size / 2.0).max(0.0) as u16;
+        for x in left..left + size as u16 {
+            if x >= projection.width || distance >= buffer.depth_in(&projection.viewport, x) { continue; }
+            for y in top..top + size as u16 {
+                buffer.set_in(&projection.viewport, x, y, color, ' ');
+            }
+        }
+    }
+}
+
+fn draw_hud(player: &Player, viewport: &Viewport, buffer: &mut RenderBuffer) -> Result<()> {
+    let term_size = (viewport.width, viewport.height);
     // Health (Bottom Left, Red)
     let health_color = Color::Rgb { r: 255, g: 0, b: 0 };
-    draw_number(buffer, 2, term_size.1 - 6, player.health, health_color);
+    draw_number(buffer, viewport, 2, term_size.1.saturating_sub(6), player.health, health_color);
+
+    // Armor (next to health, colored by tier)
+    let armor_color = match player.armor_tier {
+        ArmorTier::Blue => Color::Rgb { r: 60, g: 110, b: 255 },
+        _ => Color::Rgb { r: 40, g: 200, b: 40 },
+    };
+    if player.armor > 0 {
+        draw_number(buffer, viewport, 17, term_size.1.saturating_sub(6), player.armor, armor_color);
+    }
     
-    // Ammo (Bottom Right, Yellow)
+    // Ammo (Bottom Right, Yellow) : magazine then reserve
     let ammo_color = Color::Rgb { r: 255, g: 255, b: 0 };
-    let ammo = player.get_current_weapon().ammo;
-    draw_number(buffer, term_size.0 - 14, term_size.1 - 6, ammo, ammo_color);
+    let reserve_color = Color::Rgb { r: 170, g: 140, b: 0 };
+    let weapon = player.get_current_weapon();
+    let bottom = term_size.1.saturating_sub(6);
+    // Nothing to count for weapons without ammo
+    if weapon.ammo_type != AmmoType::None {
+        draw_number(buffer, viewport, term_size.0.saturating_sub(29), bottom, weapon.ammo, ammo_color);
+        buffer.set_in(viewport, term_size.0.saturating_sub(16), bottom + 2, Color::Reset, '/');
+        draw_number(buffer, viewport, term_size.0.saturating_sub(14), bottom, player.reserve(), reserve_color);
+    }
     
     // Kills (Top Right, Green)
     let kills_color = Color::Rgb { r: 0, g: 255, b: 0 };
-    draw_number(buffer, term_size.0 - 10, 2, player.kills, kills_color);
+    draw_number(buffer, viewport, term_size.0.saturating_sub(10), 2, player.kills, kills_color);
+
+    // Power-up timers (above health, in the color of their tint), blinking when running out
+    for (i, effect) in player.effects.iter().enumerate() {
+        if effect.blink_off() { continue; }
+        let ((r, g, b), _) = effect.power_up.tint();
+        let text = format!(" {} {:2} ", effect.power_up.label(), effect.remaining.ceil());
+        let y = term_size.1.saturating_sub(8 + i as u16);
+        for (j, ch) in text.chars().enumerate() {
+            buffer.set_in(viewport, 2 + j as u16, y, darken_color(r, g, b, 0.6), ch);
+        }
+    }
     
     Ok(())
 }
 
-fn draw_number(buffer: &mut RenderBuffer, start_x: u16, start_y: u16, number: u32, color: Color) {
+// Health bar of the closest boss fighting, across the top with its name on it
+fn draw_boss_bar(world: &World, player: &Player, viewport: &Viewport, buffer: &mut RenderBuffer) {
+    let distance = |e: &Entity| (e.transform.x - player.transform.x).hypot(e.transform.y - player.transform.y);
+    let boss = world.entities.values()
+        .filter(|e| e.active && e.health > 0 && e.ai_state != AiState::Idle)
+        .filter_map(|e| e.definition().filter(|def| def.boss).map(|def| (e, def)))
+        .min_by(|a, b| distance(a.0).total_cmp(&distance(b.0)));
+    let Some((boss, def)) = boss else { return };
+
+    let width = viewport.width / 2;
+    let start_x = (viewport.width - width) / 2;
+    let filled = (width as f64 * boss.health as f64 / def.health as f64).ceil() as u16;
+    let name_start = width.saturating_sub(def.name.chars().count() as u16) / 2;
+    let mut name = def.name.chars();
+    for x in 0..width {
+        let color = if x < filled { Color::Rgb { r: 170, g: 0, b: 0 } } else { Color::Rgb { r: 50, g: 0, b: 0 } };
+        let ch = if x >= name_start { name.next().unwrap_or(' ') } else { ' ' };
+        buffer.set_in(viewport, start_x + x, 0, color, ch);
+    }
+}
+
+// Survival : wave number (top left), score under it and the break countdown
+pub fn draw_wave_hud(waves: &Waves, viewport: &Viewport, buffer: &mut RenderBuffer) {
+    let wave_color = Color::Rgb { r: 255, g: 140, b: 0 };
+    let score_color = Color::Rgb { r: 200, g: 200, b: 200 };
+    draw_number(buffer, viewport, 2, 2, waves.wave, wave_color);
+    draw_number(buffer, viewport, 2, 8, waves.score, score_color);
+
+    if waves.phase == WavePhase::Break {
+        let background = Color::Rgb { r: 60, g: 30, b: 0 };
+        let text = format!("Wave {} in {}   (P to start now)", waves.wave + 1, waves.timer.ceil().max(0.0));
+        draw_text_centered(buffer, viewport, 2, &text, background);
+    }
+}
+
+// Survival : best runs under the death message, the run just played highlighted
+pub fn draw_high_scores(scores: &HighScores, rank: Option<usize>, viewport: &Viewport, buffer: &mut RenderBuffer) {
+    let top = viewport.height / 2 + 3;
+    let background = Color::Rgb { r: 30, g: 30, b: 30 };
+    let highlight = Color::Rgb { r: 120, g: 80, b: 0 };
+    draw_text_centered(buffer, viewport, top, "  #   SCORE  WAVE  KILLS  SKILL  ", background);
+    for (i, entry) in scores.entries.iter().enumerate() {
+        let text = format!(" {:2} {:7} {:5} {:6} {:6}  ", i + 1, entry.score, entry.wave, entry.kills, entry.skill);
+        let color = if rank == Some(i) { highlight } else { background };
+        draw_text_centered(buffer, viewport, top + 1 + i as u16, &text, color);
+    }
+}
+
+fn draw_death_screen(viewport: &Viewport, buffer: &mut RenderBuffer) {
+    let middle = viewport.height / 2;
+    let background = Color::Rgb { r: 60, g: 0, b: 0 };
+    draw_text_centered(buffer, viewport, middle.saturating_sub(1), "YOU DIED", background);
+    draw_text_centered(buffer, viewport, middle + 1, "Press FIRE to respawn", background);
+}
+
+fn draw_text_centered(buffer: &mut RenderBuffer, viewport: &Viewport, y: u16, text: &str, background: Color) {
+    let start_x = (viewport.width / 2).saturating_sub(text.chars().count() as u16 / 2);
+    for (i, ch) in text.chars().enumerate() {
+        buffer.set_in(viewport, start_x + i as u16, y, background, ch);
+    }
+}
+
+fn draw_number(buffer: &mut RenderBuffer, viewport: &Viewport, start_x: u16, start_y: u16, number: u32, color: Color) {
     let s = number.to_string();
     let mut x_offset = 0;
     
@@ -243,7 +466,7 @@ fn draw_number(buffer: &mut RenderBuffer, start_x: u16, start_y: u16, number: u3
                 if let Some(p_color) = pixel {
                     let x = i % sprite.width;
                     let y = i / sprite.width;
-                    buffer.set(start_x + x_offset + x as u16, start_y + y as u16, *p_color, ' ');
+                    buffer.set_in(viewport, start_x + x_offset + x as u16, start_y + y as u16, *p_color, ' ');
                 }
             }
             x_offset += (sprite.width + 1) as u16; // +1 for spacing
@@ -258,12 +481,13 @@ fn darken_color_by_brightness(color: Color, brightness: f64) -> Color {
     }
 }
 
-pub fn draw_weapon_sprite(player: &Player, term_size: (u16, u16), buffer: &mut RenderBuffer) -> Result<()> {
+pub fn draw_weapon_sprite(player: &Player, bob_offset: (i32, i32), viewport: &Viewport, buffer: &mut RenderBuffer) -> Result<()> {
+  let term_size = (viewport.width, viewport.height);
   let weapon_sprite = player.get_current_weapon().get_current_sprite();
   
   // Handle switching animation
   let now = std::time::Instant::now();
-  let mut y_offset = 0;
+  let mut y_offset = bob_offset.1;
   
   if now < player.switch_cooldown_expiry {
       let remaining = player.switch_cooldown_expiry.duration_since(now).as_millis() as f64;
@@ -278,12 +502,12 @@ pub fn draw_weapon_sprite(player: &Player, term_size: (u16, u16), buffer: &mut R
           // Normalize progress from 0.5-1.0 to 0.0-1.0
           let rise_progress = (progress - 0.5) * 2.0;
           let max_offset = weapon_sprite.height as i32;
-          y_offset = (max_offset as f64 * (1.0 - rise_progress)) as i32;
+          y_offset += (max_offset as f64 * (1.0 - rise_progress)) as i32;
       }
   }
 
   // Position weapon at bottom center of screen
-  let start_x = (term_size.0 / 2).saturating_sub(weapon_sprite.width as u16 / 2);
+  let start_x = (term_size.0 as i32 / 2 - weapon_sprite.width as i32 / 2 + bob_offset.0).max(0) as u16;
   let start_y = (term_size.1 as i32).saturating_sub(weapon_sprite.height as i32).saturating_add(y_offset) as u16;
 
   for (line_idx, line) in weapon_sprite.lines.iter().enumerate() {
@@ -297,7 +521,7 @@ pub fn draw_weapon_sprite(player: &Player, term_size: (u16, u16), buffer: &mut R
             Color::Rgb { r: 100, g: 100, b: 100 }
           };
           
-          buffer.set(start_x + char_idx as u16, y, color, ' ');
+          buffer.set_in(viewport, start_x + char_idx as u16, y, color, ' ');
         }
       }
     }
@@ -321,6 +545,7 @@ fn get_wall_color(distance: f64, wall_type: u8) -> Color {
     3 => (200, 180, 60),  // Yellow stone
     4 => (60, 100, 180),  // Blue stone
     5 => (160, 60, 160),  // Purple crystal
+    6 => (210, 110, 30),  // Rusty gate, see level::GATE_WALL
     _ => (120, 120, 120), // Default grey
   };
 
@@ -362,125 +587,47 @@ fn get_distance_brightness(distance: f64) -> f64 {
   else { 0.1 }
 }
 
-fn cast_wall_ray(start_x: f64, start_y: f64, angle: f64, level: &crate::level::Level) -> (f64, u8) {
-  let ray_dir_x = angle.to_radians().cos();
-  let ray_dir_y = angle.to_radians().sin();
-  
-  let mut map_x = start_x.floor() as i32;
-  let mut map_y = start_y.floor() as i32;
-
-  // Delta distance calculation (distance ray has to travel to go from 1 x-side to the next x-side)
-  let delta_dist_x = if ray_dir_x == 0.0 { 1e30 } else { (1.0 / ray_dir_x).abs() };
-  let delta_dist_y = if ray_dir_y == 0.0 { 1e30 } else { (1.0 / ray_dir_y).abs() };
-
-  let mut step_x;
-  let mut side_dist_x;
-  
-  if ray_dir_x < 0.0 {
-    step_x = -1;
-    side_dist_x = (start_x - map_x as f64) * delta_dist_x;
-  } else {
-    step_x = 1;
-    side_dist_x = (map_x as f64 + 1.0 - start_x) * delta_dist_x;
-  }
-
-  let mut step_y;
-  let mut side_dist_y;
-  
-  if ray_dir_y < 0.0 {
-    step_y = -1;
-    side_dist_y = (start_y - map_y as f64) * delta_dist_y;
-  } else {
-    step_y = 1;
-    side_dist_y = (map_y as f64 + 1.0 - start_y) * delta_dist_y;
-  }
-
-  let mut hit = false;
-  let mut side = 0; // 0 for NS, 1 for EW
-  let mut wall_type = 0;
-
-  // DDA Loop
-  let max_steps = 50;
-  let mut steps = 0;
-  
-  while !hit && steps < max_steps {
-    steps += 1;
-    // Jump to next map square, OR in x-direction, OR in y-direction
-    if side_dist_x < side_dist_y {
-      side_dist_x += delta_dist_x;
-      map_x += step_x;
-      side = 0;
-    } else {
-      side_dist_y += delta_dist_y;
-      map_y += step_y;
-      side = 1;
-    }
+use crate::entity::Transform;
 
-    // Check if ray has hit a wall
-    if map_y >= 0 && map_y < level.layout.len() as i32 && 
-       map_x >= 0 && map_x < level.layout[0].len() as i32 {
-      let w = level.layout[map_y as usize][map_x as usize];
-      if w > 0 {
-        hit = true;
-        wall_type = w;
-      }
-    } else {
-      // Hit boundary
-      hit = true;
-      wall_type = 1;
-    }
-  }
+// Distance and angle (degrees, in [-180, 180]) of a point relative to the view
+fn view_space(view: &Transform, x: f64, y: f64) -> (f64, f64) {
+  let dx = x - view.x;
+  let dy = y - view.y;
+  let distance = (dx * dx + dy * dy).sqrt();
 
-  // Calculate distance projected on camera direction (Euclidean distance would give fisheye effect!)
-  let perp_wall_dist = if side == 0 {
-    (side_dist_x - delta_dist_x)
-  } else {
-    (side_dist_y - delta_dist_y)
-  };
+  let mut relative_angle = dy.atan2(dx).to_degrees() - view.angle;
+  while relative_angle > 180.0 { relative_angle -= 360.0; }
+  while relative_angle < -180.0 { relative_angle += 360.0; }
 
-  (perp_wall_dist, wall_type)
+  (distance, relative_angle)
 }
 
-
-use crate::entity::Transform;
-
 fn project_sprite_to_screen(
-  player_transform: &Transform, 
+  view: &Transform, 
   sprite_entity: &Entity, 
-  screen_width: u16, 
-  screen_height: u16, 
-  fov: f64
+  projection: &Projection
 ) -> Option<SpriteProjection> {
+  let screen_width = projection.width;
+  let screen_height = projection.height;
+
   // Calculate distance and angle from player to sprite
-  let dx = sprite_entity.transform.x - player_transform.x;
-  let dy = sprite_entity.transform.y - player_transform.y;
-  let distance = (dx * dx + dy * dy).sqrt();
+  let (distance, relative_angle) = view_space(view, sprite_entity.transform.x, sprite_entity.transform.y);
   
   if distance < 0.1 { return None; } // Too close
   
-  // Calculate angle from player to sprite
-  let sprite_angle = dy.atan2(dx).to_degrees();
-  let mut relative_angle = sprite_angle - player_transform.angle;
-  
-  // Normalize angle to [-180, 180]
-  while relative_angle > 180.0 { relative_angle -= 360.0; }
-  while relative_angle < -180.0 { relative_angle += 360.0; }
-  
-  // Check if sprite is within FOV
-  let half_fov = fov / 2.0;
-  if relative_angle.abs() > half_fov { return None; }
-  
-  // Calculate screen x position (0.0 = left edge, 1.0 = right edge)
-  let screen_x_normalized = (relative_angle + half_fov) / fov;
-  let screen_x = screen_x_normalized * screen_width as f64;
+  // Check if sprite is within FOV and find its screen column
+  let screen_x = projection.screen_x(relative_angle)?;
+
+  // Perpendicular distance, consistent with the wall depth buffer
+  let distance = distance * relative_angle.to_radians().cos();
   
   // Calculate sprite size based on distance
-  let base_sprite_size = 1.0; // World units
-  let projected_height = (base_sprite_size * screen_height as f64) / distance;
-  let projected_width = projected_height * 0.8; // Sprites are slightly narrower than tall
+  let base_sprite_size = get_sprite_size(sprite_entity.sprite_type); // World units
+  let projected_height = base_sprite_size * projection.rows_per_unit(distance);
+  let projected_width = base_sprite_size * 0.8 * projection.columns_per_unit(distance); // Sprites are slightly narrower than tall
   
-  // Calculate screen bounds
-  let screen_y = screen_height as f64 / 2.0; // Center vertically
+  // Calculate screen bounds, every sprite stands on the floor
+  let screen_y = projection.horizon() + (0.5 - base_sprite_size / 2.0) * projection.rows_per_unit(distance);
   let left = (screen_x - projected_width / 2.0).max(0.0);
   let right = (screen_x + projected_width / 2.0).min(screen_width as f64);
   let top = (screen_y - projected_height / 2.0).max(0.0);
@@ -502,6 +649,7 @@ fn project_sprite_to_screen(
     sprite_type: sprite_entity.sprite_type,
     frame: sprite_entity.current_frame,
     state: sprite_entity.state,
+    shimmer: sprite_entity.has_effect(PowerUp::Invisibility),
   })
 }
 
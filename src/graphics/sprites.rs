@@ -26,19 +26,16 @@ impl Sprite {
 }
 
 pub fn get_sprite_frame(sprite_type: SpriteType, frame: usize, state: EntityState) -> Sprite {
-    let mut sprite = match sprite_type {
-        SpriteType::EnemyImp => {
-            let frames = [create_imp_sprite_frame1(), create_imp_sprite_frame2()];
-            frames[frame % 2].clone()
-        },
-        SpriteType::EnemyDemon => {
-            let frames = [create_demon_sprite_frame1(), create_demon_sprite_frame2()];
-            frames[frame % 2].clone()
-        },
-        SpriteType::None => create_projectile_sprite(),
-        SpriteType::ProjectilePistol => create_projectile_pistol(),
-        SpriteType::ProjectileShotgun => create_projectile_shotgun(),
-        SpriteType::ProjectileGatling => create_projectile_gatling(),
+    let mut sprite = match (sprite_type, state) {
+        // Wind-up of an enemy attack
+        (SpriteType::EnemyImp, EntityState::Attacking) => create_imp_sprite_attack(),
+        (SpriteType::EnemyDemon, EntityState::Attacking) => create_demon_sprite_bite(),
+        (SpriteType::EnemyZombieman, EntityState::Attacking) => create_zombieman_sprite_shoot(),
+        (SpriteType::EnemyCacodemon, EntityState::Attacking) => create_cacodemon_sprite(true),
+        (SpriteType::EnemyBoss, EntityState::Attacking) => create_boss_sprite_frame(frame, true),
+        // Corpses lie flat in a pool of blood
+        (_, EntityState::Dead) => create_death_frame(&get_base_frame(sprite_type, 0)),
+        _ => get_base_frame(sprite_type, frame),
     };
 
     // Apply state effects
@@ -51,7 +48,7 @@ pub fn get_sprite_frame(sprite_type: SpriteType, frame: usize, state: EntityStat
                 }
             }
         },
-        EntityState::Dying => {
+        EntityState::Dying | EntityState::Dead => {
             // Darken or turn red for death
             for pixel in sprite.pixels.iter_mut() {
                 if let Some(color) = pixel {
@@ -70,10 +67,80 @@ pub fn get_sprite_frame(sprite_type: SpriteType, frame: usize, state: EntityStat
     sprite
 }
 
+// Death frame of any sprite : squashed into its bottom third, with blood on
+// the floor around it
+fn create_death_frame(base: &Sprite) -> Sprite {
+    let (width, height) = (base.width, base.height);
+    let flat = (height / 3).max(1);
+    let blood = Some(Color::Rgb { r: 110, g: 0, b: 0 });
+    let mut pixels = vec![None; width * height];
+    for y in height - flat..height {
+        let source = (y - (height - flat)) * height / flat;
+        for x in 0..width {
+            pixels[y * width + x] = base.pixels[source * width + x];
+        }
+    }
+    for pixel in pixels[(height - 1) * width..].iter_mut() {
+        if pixel.is_none() {
+            *pixel = blood;
+        }
+    }
+    Sprite::new(width, height, pixels)
+}
+
+fn get_base_frame(sprite_type: SpriteType, frame: usize) -> Sprite {
+    match sprite_type {
+        SpriteType::EnemyImp => {
+            let frames = [create_imp_sprite_frame1(), create_imp_sprite_frame2()];
+            frames[frame % 2].clone()
+        },
+        SpriteType::EnemyDemon => {
+            let frames = [create_demon_sprite_frame1(), create_demon_sprite_frame2()];
+            frames[frame % 2].clone()
+        },
+        SpriteType::EnemyZombieman => {
+            let frames = [create_zombieman_sprite_frame1(), create_zombieman_sprite_frame2()];
+            frames[frame % 2].clone()
+        },
+        SpriteType::EnemyCacodemon => create_cacodemon_sprite(false),
+        SpriteType::EnemyBoss => create_boss_sprite_frame(frame, false),
+        SpriteType::Player => create_player_sprite(),
+        SpriteType::None => create_projectile_sprite(),
+        SpriteType::ProjectilePistol => create_projectile_pistol(),
+        SpriteType::ProjectileShotgun => create_projectile_shotgun(),
+        SpriteType::ProjectileGatling => create_projectile_gatling(),
+        SpriteType::ProjectileFireball => create_projectile_sprite(),
+        SpriteType::ProjectileRocket => create_projectile_rocket(),
+        SpriteType::PickupHealth => create_pickup_health(),
+        SpriteType::PickupArmor => create_pickup_armor((40, 160, 40), (20, 90, 20)),
+        SpriteType::PickupArmorBlue => create_pickup_armor((50, 90, 230), (20, 40, 130)),
+        SpriteType::PickupAmmo => create_pickup_ammo(),
+        SpriteType::PickupWeapon => create_pickup_weapon(),
+        SpriteType::PickupBerserk => create_pickup_sphere((200, 20, 20), (255, 150, 150)),
+        SpriteType::PickupInvulnerability => create_pickup_sphere((40, 200, 60), (200, 255, 200)),
+        SpriteType::PickupInvisibility => create_pickup_sphere((70, 70, 160), (170, 170, 230)),
+        SpriteType::PickupQuadDamage => create_pickup_sphere((130, 50, 230), (220, 180, 255)),
+    }
+}
+
 pub fn get_animation_duration(sprite_type: SpriteType) -> f64 {
     match sprite_type {
         SpriteType::EnemyImp => 0.5, // 0.5 seconds per frame
         SpriteType::EnemyDemon => 0.4,
+        SpriteType::EnemyZombieman => 0.6,
+        SpriteType::EnemyBoss => 0.5,
+        _ => 1.0,
+    }
+}
+
+// Height of the sprite in world units, small ones lie on the floor
+pub fn get_sprite_size(sprite_type: SpriteType) -> f64 {
+    match sprite_type {
+        SpriteType::PickupHealth | SpriteType::PickupAmmo => 0.35,
+        SpriteType::PickupArmor | SpriteType::PickupArmorBlue | SpriteType::PickupWeapon => 0.45,
+        SpriteType::PickupBerserk | SpriteType::PickupInvulnerability
+            | SpriteType::PickupInvisibility | SpriteType::PickupQuadDamage => 0.45,
+        SpriteType::EnemyBoss => 1.4,
         _ => 1.0,
     }
 }
@@ -214,6 +281,318 @@ fn create_demon_sprite_frame2() -> Sprite {
     Sprite::new(width, height, pixels)
 }
 
+fn create_imp_sprite_attack() -> Sprite {
+    // 8x8 Imp Sprite (Attack - Arm raised with a fireball)
+    let width = 8;
+    let height = 8;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 139, g: 69, b: 19 }); // SaddleBrown
+    let c2 = Some(Color::Rgb { r: 205, g: 133, b: 63 }); // Peru (lighter)
+    let c3 = Some(Color::Rgb { r: 255, g: 69, b: 0 });   // RedOrange (eyes)
+    let c4 = Some(Color::Rgb { r: 255, g: 215, b: 0 });  // Gold (fireball)
+
+    let pattern = [
+        0, 0, 1, 1, 1, 1, 4, 4,
+        0, 1, 1, 1, 1, 1, 4, 4,
+        1, 1, 3, 1, 1, 3, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 0,
+        0, 1, 2, 2, 2, 2, 0, 0,
+        0, 1, 2, 1, 1, 2, 0, 0,
+        0, 1, 1, 0, 0, 1, 1, 0,
+        0, 1, 0, 0, 0, 0, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_demon_sprite_bite() -> Sprite {
+    // 10x10 Demon Sprite (Bite - Jaws wide open)
+    let width = 10;
+    let height = 10;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 178, g: 34, b: 34 });
+    let c2 = Some(Color::Rgb { r: 255, g: 105, b: 180 });
+    let c3 = Some(Color::Rgb { r: 50, g: 205, b: 50 });
+    let c4 = Some(Color::Rgb { r: 240, g: 240, b: 220 }); // Teeth
+
+    let pattern = [
+        0, 0, 0, 1, 1, 1, 1, 0, 0, 0,
+        0, 0, 1, 1, 2, 2, 1, 1, 0, 0,
+        0, 1, 3, 1, 1, 1, 1, 3, 1, 0,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 4, 0, 4, 0, 0, 4, 0, 4, 1, // Upper teeth
+        1, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        1, 4, 0, 4, 0, 0, 4, 0, 4, 1, // Lower teeth
+        0, 1, 1, 1, 2, 2, 1, 1, 1, 0,
+        0, 1, 0, 0, 1, 1, 0, 0, 1, 0,
+        0, 0, 0, 0, 1, 1, 0, 0, 0, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_zombieman_sprite_frame1() -> Sprite {
+    // 8x8 Zombieman Sprite (Frame 1 - Rifle held low)
+    let width = 8;
+    let height = 8;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 85, g: 107, b: 47 });  // DarkOliveGreen (uniform)
+    let c2 = Some(Color::Rgb { r: 188, g: 143, b: 143 }); // RosyBrown (skin)
+    let c3 = Some(Color::Rgb { r: 200, g: 30, b: 30 });   // Red (eyes)
+    let c4 = Some(Color::Rgb { r: 60, g: 60, b: 60 });    // Rifle
+
+    let pattern = [
+        0, 0, 2, 2, 2, 2, 0, 0,
+        0, 0, 3, 2, 2, 3, 0, 0,
+        0, 0, 2, 2, 2, 2, 0, 0,
+        0, 1, 1, 1, 1, 1, 1, 0,
+        1, 1, 1, 1, 4, 4, 4, 4,
+        0, 1, 1, 1, 1, 1, 0, 0,
+        0, 0, 1, 0, 0, 1, 0, 0,
+        0, 1, 1, 0, 0, 1, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_zombieman_sprite_frame2() -> Sprite {
+    // 8x8 Zombieman Sprite (Frame 2 - Shambling)
+    let width = 8;
+    let height = 8;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 85, g: 107, b: 47 });
+    let c2 = Some(Color::Rgb { r: 188, g: 143, b: 143 });
+    let c3 = Some(Color::Rgb { r: 200, g: 30, b: 30 });
+    let c4 = Some(Color::Rgb { r: 60, g: 60, b: 60 });
+
+    let pattern = [
+        0, 0, 2, 2, 2, 2, 0, 0,
+        0, 0, 3, 2, 2, 3, 0, 0,
+        0, 0, 2, 2, 2, 2, 0, 0,
+        0, 1, 1, 1, 1, 1, 1, 0,
+        1, 1, 1, 1, 4, 4, 4, 4,
+        0, 1, 1, 1, 1, 1, 0, 0,
+        0, 1, 0, 0, 0, 0, 1, 0, // Legs move
+        0, 1, 0, 0, 0, 0, 1, 1,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_zombieman_sprite_shoot() -> Sprite {
+    // 8x8 Zombieman Sprite (Attack - Rifle raised and firing)
+    let width = 8;
+    let height = 8;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 85, g: 107, b: 47 });
+    let c2 = Some(Color::Rgb { r: 188, g: 143, b: 143 });
+    let c3 = Some(Color::Rgb { r: 200, g: 30, b: 30 });
+    let c4 = Some(Color::Rgb { r: 60, g: 60, b: 60 });
+    let c5 = Some(Color::Rgb { r: 255, g: 230, b: 120 }); // Muzzle flash
+
+    let pattern = [
+        0, 0, 2, 2, 2, 2, 0, 0,
+        0, 0, 3, 2, 2, 3, 0, 0,
+        0, 0, 2, 2, 4, 4, 4, 5,
+        0, 1, 1, 1, 1, 1, 5, 5,
+        1, 1, 1, 1, 1, 1, 0, 0,
+        0, 1, 1, 1, 1, 1, 0, 0,
+        0, 0, 1, 0, 0, 1, 0, 0,
+        0, 1, 1, 0, 0, 1, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            5 => c5,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_cacodemon_sprite(mouth_open: bool) -> Sprite {
+    // 10x10 Cacodemon Sprite (floating ball with one eye)
+    let width = 10;
+    let height = 10;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 170, g: 20, b: 40 });  // Crimson (skin)
+    let c2 = Some(Color::Rgb { r: 110, g: 10, b: 25 });  // Darker (horns)
+    let c3 = Some(Color::Rgb { r: 60, g: 200, b: 60 });  // Green (eye)
+    let c4 = Some(Color::Rgb { r: 240, g: 240, b: 220 }); // Teeth
+
+    let mut pattern = [
+        0, 2, 0, 1, 1, 1, 1, 0, 2, 0,
+        0, 0, 1, 1, 1, 1, 1, 1, 0, 0,
+        0, 1, 1, 1, 3, 3, 1, 1, 1, 0,
+        1, 1, 1, 3, 3, 3, 3, 1, 1, 1,
+        1, 1, 1, 1, 3, 3, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 4, 4, 4, 4, 4, 4, 1, 1,
+        0, 1, 1, 1, 1, 1, 1, 1, 1, 0,
+        0, 0, 1, 1, 1, 1, 1, 1, 0, 0,
+        0, 0, 0, 1, 1, 1, 1, 0, 0, 0,
+    ];
+    if mouth_open {
+        // Jaws apart with a fireball in between
+        pattern[60..70].copy_from_slice(&[1, 4, 0, 4, 0, 0, 4, 0, 4, 1]);
+        pattern[70..80].copy_from_slice(&[0, 1, 0, 0, 5, 5, 0, 0, 1, 0]);
+        pattern[80..90].copy_from_slice(&[0, 0, 4, 0, 4, 4, 0, 4, 0, 0]);
+    }
+    let c5 = Some(Color::Rgb { r: 255, g: 140, b: 0 }); // Fireball
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            5 => c5,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_boss_sprite_frame(frame: usize, attacking: bool) -> Sprite {
+    // 12x12 Baron Sprite (horned goat-legged brute)
+    let width = 12;
+    let height = 12;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 205, g: 170, b: 140 }); // Pale flesh
+    let c2 = Some(Color::Rgb { r: 139, g: 90, b: 43 });   // Brown fur (legs)
+    let c3 = Some(Color::Rgb { r: 230, g: 230, b: 200 }); // Horns
+    let c4 = Some(Color::Rgb { r: 255, g: 40, b: 0 });    // Eyes
+    let c5 = Some(Color::Rgb { r: 60, g: 220, b: 60 });   // Green fire in hand
+
+    let mut pattern = [
+        0, 0, 3, 0, 0, 0, 0, 0, 0, 3, 0, 0,
+        0, 0, 3, 3, 1, 1, 1, 1, 3, 3, 0, 0,
+        0, 0, 0, 1, 4, 1, 1, 4, 1, 0, 0, 0,
+        0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 0,
+        0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0,
+        1, 1, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1,
+        1, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 1,
+        0, 0, 0, 2, 2, 2, 2, 2, 2, 0, 0, 0,
+        0, 0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 0,
+        0, 0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 0,
+        0, 0, 2, 2, 0, 0, 0, 0, 2, 2, 0, 0,
+        0, 0, 2, 2, 0, 0, 0, 0, 2, 2, 0, 0,
+    ];
+    if frame % 2 == 1 {
+        // Legs move
+        pattern[120..132].copy_from_slice(&[0, 0, 2, 2, 0, 0, 0, 0, 0, 2, 2, 0]);
+        pattern[132..144].copy_from_slice(&[0, 2, 2, 0, 0, 0, 0, 0, 0, 2, 2, 0]);
+    }
+    if attacking {
+        // Arm raised over the head, holding a ball of fire
+        pattern[0..12].copy_from_slice(&[0, 0, 3, 0, 0, 0, 0, 0, 0, 3, 5, 5]);
+        pattern[12..24].copy_from_slice(&[0, 0, 3, 3, 1, 1, 1, 1, 3, 3, 5, 5]);
+        pattern[24..36].copy_from_slice(&[0, 0, 0, 1, 4, 1, 1, 4, 1, 0, 1, 0]);
+        pattern[36..48].copy_from_slice(&[0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 0]);
+        pattern[60..72].copy_from_slice(&[1, 1, 0, 1, 1, 1, 1, 1, 1, 0, 0, 0]);
+        pattern[72..84].copy_from_slice(&[1, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 0]);
+    }
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            4 => c4,
+            5 => c5,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_player_sprite() -> Sprite {
+    // 8x8 Marine (other players)
+    let width = 8;
+    let height = 8;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 34, g: 139, b: 34 });  // Green armor
+    let c2 = Some(Color::Rgb { r: 210, g: 160, b: 120 }); // Skin
+    let c3 = Some(Color::Rgb { r: 90, g: 90, b: 90 });    // Gun metal
+
+    let pattern = [
+        0, 0, 1, 1, 1, 1, 0, 0,
+        0, 0, 1, 2, 2, 1, 0, 0,
+        0, 1, 1, 1, 1, 1, 1, 0,
+        1, 1, 1, 1, 1, 3, 3, 3,
+        1, 0, 1, 1, 1, 1, 0, 0,
+        0, 0, 1, 1, 1, 1, 0, 0,
+        0, 0, 1, 0, 0, 1, 0, 0,
+        0, 1, 1, 0, 0, 1, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            3 => c3,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
 fn create_projectile_sprite() -> Sprite {
     // 4x4 Projectile (Fireball)
     let width = 4;
@@ -313,6 +692,168 @@ fn create_projectile_gatling() -> Sprite {
     Sprite::new(width, height, pixels)
 }
 
+fn create_projectile_rocket() -> Sprite {
+    // 4x3 Rocket with its exhaust
+    let width = 4;
+    let height = 3;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 150, g: 150, b: 140 }); // Casing
+    let c2 = Some(Color::Rgb { r: 255, g: 160, b: 30 });  // Exhaust
+
+    let pattern = [
+        0, 1, 1, 0,
+        2, 1, 1, 1,
+        0, 1, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_pickup_health() -> Sprite {
+    // 5x5 White box with a red cross
+    let width = 5;
+    let height = 5;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 230, g: 230, b: 230 }); // White
+    let c2 = Some(Color::Rgb { r: 220, g: 20, b: 20 });   // Red
+
+    let pattern = [
+        1, 1, 2, 1, 1,
+        1, 1, 2, 1, 1,
+        2, 2, 2, 2, 2,
+        1, 1, 2, 1, 1,
+        1, 1, 2, 1, 1,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_pickup_armor((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> Sprite {
+    // 5x5 Vest, colored by tier
+    let width = 5;
+    let height = 5;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: r1, g: g1, b: b1 }); // Body
+    let c2 = Some(Color::Rgb { r: r2, g: g2, b: b2 }); // Plates
+
+    let pattern = [
+        1, 1, 0, 1, 1,
+        1, 2, 1, 2, 1,
+        1, 1, 1, 1, 1,
+        1, 2, 1, 2, 1,
+        0, 1, 1, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_pickup_ammo() -> Sprite {
+    // 4x3 Ammo box
+    let width = 4;
+    let height = 3;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 120, g: 90, b: 40 });  // Brown
+    let c2 = Some(Color::Rgb { r: 230, g: 190, b: 60 }); // Brass
+
+    let pattern = [
+        2, 2, 2, 2,
+        1, 1, 1, 1,
+        1, 1, 1, 1,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_pickup_weapon() -> Sprite {
+    // 6x3 Gun lying on the floor
+    let width = 6;
+    let height = 3;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: 110, g: 110, b: 120 }); // Steel
+    let c2 = Some(Color::Rgb { r: 90, g: 60, b: 30 });    // Wood
+
+    let pattern = [
+        0, 0, 0, 0, 0, 0,
+        1, 1, 1, 1, 1, 1,
+        2, 2, 0, 0, 0, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
+fn create_pickup_sphere((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> Sprite {
+    // 5x5 Glowing sphere, colored by power-up
+    let width = 5;
+    let height = 5;
+    let mut pixels = vec![None; width * height];
+
+    let c1 = Some(Color::Rgb { r: r1, g: g1, b: b1 }); // Body
+    let c2 = Some(Color::Rgb { r: r2, g: g2, b: b2 }); // Shine
+
+    let pattern = [
+        0, 1, 1, 1, 0,
+        1, 2, 2, 1, 1,
+        1, 2, 1, 1, 1,
+        1, 1, 1, 1, 1,
+        0, 1, 1, 1, 0,
+    ];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        pixels[i] = match p {
+            1 => c1,
+            2 => c2,
+            _ => None,
+        };
+    }
+
+    Sprite::new(width, height, pixels)
+}
+
 pub fn get_digit_sprite(digit: u32, color: Color) -> Sprite {
     let width = 3;
     let height = 5;
@@ -0,0 +1,82 @@
+/* Best survival runs, kept in highscores.toml in the working directory. */
+
+use std::io::{Error, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+
+pub const HIGH_SCORES_PATH: &str = "highscores.toml";
+// Runs kept in the table
+pub const MAX_HIGH_SCORES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub score: u32,
+    pub wave: u32,
+    pub kills: u32,
+    pub skill: u8,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    // Best first
+    #[serde(default, rename = "score")]
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    pub fn load() -> Result<Self> {
+        match std::fs::read_to_string(HIGH_SCORES_PATH) {
+            Ok(text) => Self::parse(&text)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", HIGH_SCORES_PATH, e))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn parse(text: &str) -> std::result::Result<Self, toml::de::Error> {
+        let mut scores: Self = toml::from_str(text)?;
+        scores.entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        scores.entries.truncate(MAX_HIGH_SCORES);
+        Ok(scores)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let text = toml::to_string(self).map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        std::fs::write(HIGH_SCORES_PATH, text)
+    }
+
+    // Add a run, returns its rank from 0 if it made the table
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        // Ties go after the older runs
+        let rank = self.entries.iter().position(|e| e.score < entry.score).unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(score: u32) -> ScoreEntry {
+        ScoreEntry { score, wave: 1, kills: 0, skill: 3 }
+    }
+
+    #[test]
+    fn table_stays_sorted_and_bounded() {
+        let mut scores = HighScores::default();
+        for score in 0..MAX_HIGH_SCORES as u32 {
+            assert!(scores.insert(run(score * 100)).is_some());
+        }
+        assert_eq!(scores.insert(run(250)), Some(7));
+        assert_eq!(scores.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(scores.insert(run(0)), None);
+
+        let reloaded = HighScores::parse(&toml::to_string(&scores).unwrap()).unwrap();
+        assert_eq!(reloaded.entries, scores.entries);
+        assert_eq!(reloaded.entries[0].score, 900);
+    }
+}
@@ -39,24 +39,49 @@ use weapon::Weapon;
 mod player;
 use player::Player;
 
-use modes::{SoloGame, HostGame, ClientGame, GameMode};
+mod config;
+use config::Config;
+
+mod highscores;
+
+use modes::{SoloGame, HostGame, ClientGame, CoopGame, SurvivalGame, GameMode};
 
 // Main program loop
 fn main() -> Result<()>{
+  // Parse arguments and settings before touching the terminal
+  let args: Vec<String> = std::env::args().collect();
+  let config = Config::load(&args)?;
+  weapon::WEAPONS.load()?;
+  common::enemies::ENEMIES.load()?;
+
   // Initialize terminal
   terminal_init()?;
 
+  // Replay a recording instead of playing
+  if let Some(path) = config::parse_arg::<String>(&args, "--play")? {
+    let result = graphics::recording::play(&path);
+    terminal_cleanup()?;
+    return result;
+  }
+
+  if let Some(path) = config::parse_arg::<String>(&args, "--record")? {
+    graphics::recording::start(&path, terminal::size()?, config.skill)?;
+  }
+
   // Load level
   let level = level::Level::debug_1()?;
   
-  // Parse arguments
-  let args: Vec<String> = std::env::args().collect();
   let mut game: Box<dyn GameMode> = if args.contains(&"--host".to_string()) {
-      Box::new(HostGame::new(level)?)
+      Box::new(HostGame::new(level, config)?)
   } else if args.contains(&"--client".to_string()) {
-      Box::new(ClientGame::new()?)
+      Box::new(ClientGame::new(config)?)
+  } else if args.contains(&"--coop".to_string()) {
+      let players = config::parse_arg(&args, "--players")?.unwrap_or(2);
+      Box::new(CoopGame::new(level, config, players)?)
+  } else if args.contains(&"--survival".to_string()) {
+      Box::new(SurvivalGame::new(level, config)?)
   } else {
-      Box::new(SoloGame::new(level)?)
+      Box::new(SoloGame::new(level, config)?)
   };
 
   let mut input_manager = InputManager::new();
@@ -82,6 +107,7 @@ fn main() -> Result<()>{
   }
 
   // clean the terminal before ending the program
+  graphics::recording::stop()?;
   terminal_cleanup()?;
   Ok(())
 }
@@ -1,4 +1,4 @@
-use std::io::Result;
+use std::io::{Error, ErrorKind, Result};
 use renet::{RenetClient, ConnectionConfig};
 use matchbox_socket::WebRtcSocket;
 use futures::future::BoxFuture;
@@ -7,8 +7,12 @@ use crate::common::level::Level;
 use crate::network::connection::setup_client;
 use crate::input::{InputManager, Action};
 use crate::graphics::RenderBuffer;
+use crate::common::events::GameEvent;
+use crate::common::data::definitions_hash;
 use crate::common::protocol::{ClientMessage, ServerMessage, PlayerInput};
 use crate::graphics::draw;
+use crate::graphics::{ParticleSystem, ScreenEffects, Projection, Viewport};
+use crate::config::Config;
 use crossterm::terminal;
 
 use crate::player::Player;
@@ -21,11 +25,16 @@ pub struct ClientGame {
     pub render_buffer: RenderBuffer,
     pub term_size: (u16, u16),
     pub player: Player,
+    pub player_id: Option<u32>,
     pub level: Level,
+    pub particles: ParticleSystem,
+    pub effects: ScreenEffects,
+    pub config: Config,
+    pub time_of_last_loop: std::time::Instant,
 }
 
 impl ClientGame {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: Config) -> Result<Self> {
         let runtime = tokio::runtime::Runtime::new()?;
         let (client, socket, message_loop) = setup_client();
         
@@ -45,7 +54,12 @@ impl ClientGame {
             render_buffer,
             term_size: (w, h),
             player,
+            player_id: None,
             level,
+            particles: ParticleSystem::new(),
+            effects: ScreenEffects::new(),
+            config,
+            time_of_last_loop: std::time::Instant::now(),
         })
     }
 }
@@ -69,7 +83,9 @@ impl GameMode for ClientGame {
             rotate_right: input_manager.is_active(Action::RotateRight),
             shoot: input_manager.is_active(Action::Shoot),
             reload: input_manager.is_active(Action::Reload),
+            respawn: self.player.wants_respawn(input_manager.is_active(Action::RespawnPlayer)),
             view_angle: 0.0, // TODO: Get from player entity if we have one
+            inventory: self.player.inventory(),
         };
         
         let message = ClientMessage::Input(input);
@@ -81,12 +97,54 @@ impl GameMode for ClientGame {
         while let Some(message) = self.client.receive_message(0) {
             if let Ok(msg) = bincode::deserialize::<ServerMessage>(&message) {
                 match msg {
+                    ServerMessage::Welcome { player_id, data_hash } => {
+                        // Entities name weapons and monsters by their index in the data files
+                        if data_hash != definitions_hash() {
+                            return Err(Error::new(ErrorKind::InvalidData, "weapons.toml or enemies.toml differ from the host's"));
+                        }
+                        self.player_id = Some(player_id);
+                    }
                     ServerMessage::WorldSnapshot(snapshot) => {
                         self.world = snapshot;
                     }
+                    ServerMessage::Event(event) => {
+                        self.particles.handle_event(&event);
+                        match event {
+                            // The host only knows our health and armor, ammo and weapons are ours to add
+                            GameEvent::ItemPickup { player_id, kind, .. } if Some(player_id) == self.player_id => {
+                                self.player.give(kind);
+                                self.effects.trigger_pickup();
+                            }
+                            GameEvent::Explosion { x, y, radius } => self.effects.trigger_explosion((x, y), radius, &self.player.transform),
+                            GameEvent::EnemyKilled { killer } if Some(killer) == self.player_id => self.player.kills += 1,
+                            _ => {}
+                        }
+                    }
                 }
             }
         }
+
+        // Gates follow the host's world
+        self.level.update_gates(self.world.gates_open, &self.world.occupied_cells());
+
+        // Sync player struct with our entity (for rendering)
+        if let Some(entity) = self.player_id.and_then(|id| self.world.get_entity(id)) {
+            self.player.transform = entity.transform;
+            let health = entity.health.max(0) as u32;
+            if self.player.is_dead() && health > 0 {
+                // The host respawned us
+                self.player.respawn(true);
+            }
+            self.player.sync_with(entity);
+            if self.player.is_dead() && self.player.death_time.is_none() {
+                self.player.death_time = Some(std::time::Instant::now());
+            }
+        }
+
+        let now = std::time::Instant::now();
+        let delta_time = now.duration_since(self.time_of_last_loop).as_secs_f64();
+        self.time_of_last_loop = now;
+        self.particles.update(delta_time, &self.level);
         
         // Render
         let mut stdout = std::io::stdout();
@@ -95,7 +153,9 @@ impl GameMode for ClientGame {
              self.render_buffer = RenderBuffer::new(self.term_size.0, self.term_size.1);
         }
         
-        draw(&self.world, &self.player, &self.level, self.term_size, &mut self.render_buffer)?;
+        self.effects.update(&self.player);
+        let projection = Projection::new(&self.config, Viewport::full(self.term_size));
+        draw(&self.world, &self.player, &self.level, &self.particles, &self.effects, &projection, &mut self.render_buffer)?;
         self.render_buffer.flush(&mut stdout)?;
         
         Ok(false)
@@ -0,0 +1,187 @@
+/* Controls of a player playing on this machine : movement, weapons and the
+sync of the Player struct with its entity. Shared by every mode that has
+local players. */
+
+use crate::common::events::GameEvent;
+use crate::common::level::Level;
+use crate::common::physics::Physics;
+use crate::common::protocol::Inventory;
+use crate::common::powerups::{PowerUp, BERSERK_MELEE, QUAD_DAMAGE};
+use crate::common::world::World;
+use crate::entity::Transform;
+use crate::input::Action;
+use crate::player::Player;
+use crate::weapon::{FireMode, SoundCue, WeaponState};
+use std::time::Instant;
+
+const DEATH_CAMERA_SPEED: f64 = 120.0; // Degrees per second
+
+// Returns true when a dead player asks to respawn, the mode decides how
+pub fn update_local_player(world: &mut World, level: &Level, player: &mut Player, is_active: impl Fn(Action) -> bool, delta_time: f64) -> bool {
+    let player_id = player.entity_id;
+
+    // Health and armor live in the entity, enemies damage it
+    if let Some(entity) = world.get_entity(player_id) {
+        player.sync_with(entity);
+    }
+
+    if player.is_dead() {
+        if player.death_time.is_none() {
+            player.death_time = Some(Instant::now());
+        }
+        death_camera(world, player_id, delta_time);
+        if let Some(entity) = world.get_entity(player_id) {
+            player.transform = entity.transform;
+        }
+        return player.wants_respawn(is_active(Action::RespawnPlayer));
+    }
+
+    // Player Movement
+    let mut move_speed = if is_active(Action::Sprint) {
+        crate::common::entity::PLAYER_SPEED * 2.0
+    } else {
+        crate::common::entity::PLAYER_SPEED
+    };
+
+    let mut rot_speed = crate::common::entity::PLAYER_ROTATION_SPEED;
+
+    // Apply weapon weight penalty, and berserk
+    let penalty = player.get_current_weapon().movement_penalty;
+    move_speed *= 1.0 - penalty;
+    move_speed *= world.get_entity(player_id).map_or(1.0, |e| e.speed_scale());
+    rot_speed *= 1.0 - (penalty * 0.5); // Less penalty on rotation
+
+    if is_active(Action::MoveForward) {
+        Physics::move_entity_forward(world, player_id, move_speed * delta_time, level);
+    }
+    if is_active(Action::MoveBackward) {
+        Physics::move_entity_forward(world, player_id, -move_speed * delta_time, level);
+    }
+    if is_active(Action::RotateLeft) {
+        Physics::rotate_entity(world, player_id, -rot_speed * delta_time);
+    }
+    if is_active(Action::RotateRight) {
+        Physics::rotate_entity(world, player_id, rot_speed * delta_time);
+    }
+    if is_active(Action::StrafeLeft) {
+        Physics::strafe_entity(world, player_id, -move_speed * delta_time, level);
+    }
+    if is_active(Action::StrafeRight) {
+        Physics::strafe_entity(world, player_id, move_speed * delta_time, level);
+    }
+
+    // Sync player struct with entity (for rendering)
+    if let Some(entity) = world.get_entity(player_id) {
+        player.transform = entity.transform;
+        player.sync_with(entity);
+    }
+
+    // Weapon handling
+    let was_reloading = player.get_current_weapon().state == WeaponState::Reloading;
+    if is_active(Action::Shoot) && player.fire() {
+        weapon_sound(world, player, SoundCue::Fire);
+        // Fire every pellet of the shot
+        let weapon = player.get_current_weapon();
+        let fire_mode = weapon.fire_mode;
+        let count = weapon.projectile_count;
+        let spread = weapon.spread;
+        let mut damage = weapon.damage as f64;
+        let range = weapon.range;
+        let sprite_type = weapon.projectile_sprite;
+        let splash = weapon.splash;
+        let melee_arc = weapon.melee_arc;
+        if let Some(entity) = world.get_entity(player_id) {
+            if entity.has_effect(PowerUp::QuadDamage) {
+                damage *= QUAD_DAMAGE;
+            }
+            if fire_mode == FireMode::Melee && entity.has_effect(PowerUp::Berserk) {
+                damage *= BERSERK_MELEE;
+            }
+        }
+        let damage = damage.round() as i32;
+
+        let radians = player.transform.angle.to_radians();
+        let gun_offset = 0.5; // Distance from player center to gun barrel
+        let spawn_x = player.transform.x + radians.cos() * gun_offset;
+        let spawn_y = player.transform.y + radians.sin() * gun_offset;
+        let angle = player.transform.angle;
+        if fire_mode != FireMode::Melee {
+            world.push_event(GameEvent::WeaponFired { x: spawn_x, y: spawn_y, angle });
+        }
+
+        for i in 0..count {
+            let angle_offset = if count > 1 {
+                (i as f64 - (count as f64 - 1.0) / 2.0) * spread
+            } else {
+                0.0
+            };
+
+            // Instant shots start from the player so a wall touching the barrel still stops them
+            let ray = Transform::new(player.transform.x, player.transform.y, angle + angle_offset);
+            match fire_mode {
+                FireMode::Hitscan => { Physics::hitscan(world, level, player_id, ray, range, damage, true); }
+                FireMode::Melee => { Physics::melee(world, level, player_id, range, melee_arc, damage); }
+                FireMode::Projectile => {
+                    let projectile = world.spawn_projectile(player_id, Transform::new(spawn_x, spawn_y, angle + angle_offset), damage, range, sprite_type);
+                    if let Some(projectile) = world.get_entity_mut(projectile) {
+                        projectile.splash = splash;
+                    }
+                }
+            }
+        }
+    }
+    if is_active(Action::Reload) {
+        player.reload();
+    }
+    // Asked for, or started by firing an empty magazine
+    if !was_reloading && player.get_current_weapon().state == WeaponState::Reloading {
+        weapon_sound(world, player, SoundCue::Reload);
+    }
+
+    // Weapon switching
+    if is_active(Action::SwitchWeapon1) { player.switch_weapon(1); }
+    if is_active(Action::SwitchWeapon2) { player.switch_weapon(2); }
+    if is_active(Action::SwitchWeapon3) { player.switch_weapon(3); }
+    if is_active(Action::SwitchWeapon4) { player.switch_weapon(4); }
+    if is_active(Action::SwitchWeapon5) { player.switch_weapon(5); }
+
+    false
+}
+
+// Tell the audio backend, the sound names are in the weapon's definition
+fn weapon_sound(world: &mut World, player: &Player, cue: SoundCue) {
+    if let Some(weapon) = crate::weapon::index_of(&player.get_current_weapon().id) {
+        world.push_event(GameEvent::WeaponSound { x: player.transform.x, y: player.transform.y, weapon, cue });
+    }
+}
+
+// Let a player take an item it touches. Health and armor go to the entity,
+// ammo and weapons to the Player : directly for local players, remote ones
+// get them from the ItemPickup event on their client. `inventory` is what a
+// remote player last reported carrying.
+pub fn pick_up(world: &mut World, player_id: u32, pickup_id: u32, local: Option<&mut Player>, inventory: Option<&Inventory>) {
+    let Some(kind) = world.get_entity(pickup_id).and_then(|p| p.pickup) else { return };
+    let Some(entity) = world.get_entity_mut(player_id) else { return };
+    let (x, y) = (entity.transform.x, entity.transform.y);
+
+    let healed = entity.pick_up(kind);
+    let given = match local {
+        Some(player) => player.give(kind),
+        // Nothing reported yet : let it have the item
+        None => inventory.cloned().unwrap_or_default().wants(kind),
+    };
+    if healed || given {
+        world.take_pickup(pickup_id);
+        world.push_event(GameEvent::ItemPickup { x, y, player_id, kind });
+    }
+}
+
+// Slowly turn a dead player toward whoever killed them
+pub fn death_camera(world: &mut World, player_id: u32, delta_time: f64) {
+    let Some(entity) = world.get_entity(player_id) else { return };
+    let Some(killer) = entity.last_attacker.and_then(|id| world.get_entity(id)) else { return };
+    let wanted = (killer.transform.y - entity.transform.y).atan2(killer.transform.x - entity.transform.x).to_degrees();
+    let diff = (wanted - entity.transform.angle + 540.0).rem_euclid(360.0) - 180.0;
+    let max_turn = DEATH_CAMERA_SPEED * delta_time;
+    Physics::rotate_entity(world, player_id, diff.clamp(-max_turn, max_turn));
+}
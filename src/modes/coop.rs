@@ -0,0 +1,169 @@
+/* Local co-op : two to four players share one world and one terminal, each
+with their own keys, viewport and HUD. */
+
+use std::io::{Write, Result};
+use std::time::{Duration, Instant};
+use crossterm::terminal;
+use crate::level::Level;
+use crate::player::Player;
+use crate::world::World;
+use crate::entity::Entity;
+use crate::graphics::RenderBuffer;
+use crate::graphics::draw;
+use crate::graphics::{ParticleSystem, ScreenEffects, Projection, Viewport};
+use crate::config::Config;
+use crate::input::{InputManager, Action, KeyBindings};
+use crate::common::physics::Physics;
+use crate::common::events::GameEvent;
+use crate::common::ai::Ai;
+use crate::common::pathfinding::Navigation;
+use crate::modes::controls;
+
+pub const MAX_LOCAL_PLAYERS: usize = 4;
+
+pub struct LocalPlayer {
+    pub player: Player,
+    pub bindings: KeyBindings,
+    pub effects: ScreenEffects,
+}
+
+pub struct CoopGame {
+    pub time_of_last_loop: Instant,
+    pub players: Vec<LocalPlayer>,
+    pub world: World,
+    pub level: Level,
+    pub term_size: (u16, u16),
+    pub target_fps: u32,
+    pub render_buffer: RenderBuffer,
+    pub particles: ParticleSystem,
+    pub navigation: Navigation,
+    pub config: Config,
+}
+
+impl CoopGame {
+    pub fn new(level: Level, config: Config, player_count: usize) -> Result<Self> {
+        let term_size = terminal::size()?;
+        let render_buffer = RenderBuffer::new(term_size.0, term_size.1);
+
+        let mut world = World::new();
+        world.skill = config.skill;
+        world.corpse_limit = config.corpse_limit.unwrap_or(level.corpse_limit);
+
+        // Create one entity per local player, side by side at the start
+        let player_count = player_count.clamp(2, MAX_LOCAL_PLAYERS);
+        let mut players = Vec::new();
+        for i in 0..player_count {
+            let player_entity = Entity::new_player(0, 3.5 + i as f64, 3.5);
+            let mut player = Player::new()?;
+            player.entity_id = world.spawn_entity(player_entity);
+            players.push(LocalPlayer {
+                player,
+                bindings: KeyBindings::local_player(i),
+                effects: ScreenEffects::new(),
+            });
+        }
+
+        // Enemies and items of the level, taken items come back
+        world.spawn_things(&level);
+        world.respawn_pickups = true;
+
+        Ok(Self {
+            time_of_last_loop: Instant::now(),
+            players,
+            world,
+            level,
+            term_size,
+            target_fps: 30,
+            render_buffer,
+            particles: ParticleSystem::new(),
+            navigation: Navigation::new(),
+            config,
+        })
+    }
+}
+
+use crate::modes::gamemode::GameMode;
+
+impl GameMode for CoopGame {
+    fn update(&mut self, input_manager: &InputManager) -> Result<bool> {
+        if input_manager.is_active(Action::Quit) {
+            return Ok(true);
+        }
+
+        self.term_size = terminal::size()?;
+
+        // Calculate delta time
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.time_of_last_loop).as_secs_f64();
+        self.time_of_last_loop = now;
+
+        // Each player reads its own keys
+        for (i, local) in self.players.iter_mut().enumerate() {
+            let bindings = &local.bindings;
+            if controls::update_local_player(&mut self.world, &self.level, &mut local.player, |action| input_manager.is_active_with(bindings, action), delta_time) {
+                // Back at the start with the starting loadout, the team keeps its kills
+                self.world.respawn_player(local.player.entity_id, 3.5 + i as f64, 3.5);
+                local.player.respawn(true);
+            }
+            local.player.animation_update();
+        }
+
+        // Enemies think before the world moves
+        self.navigation.update(&self.world, &self.level);
+        Ai::update(&mut self.world, &self.level, &self.navigation, delta_time);
+
+        // Update world physics, kills are shared in co-op
+        let kills = Physics::update(&mut self.world, delta_time, &self.level);
+        for local in self.players.iter_mut() {
+            local.player.kills += kills;
+        }
+        if self.level.update_gates(self.world.gates_open, &self.world.occupied_cells()) {
+            self.navigation = Navigation::new();
+        }
+
+        for (player_id, pickup_id) in Physics::touched_pickups(&self.world) {
+            let local = self.players.iter_mut().find(|l| l.player.entity_id == player_id).map(|l| &mut l.player);
+            controls::pick_up(&mut self.world, player_id, pickup_id, local, None);
+        }
+
+        for event in self.world.drain_events() {
+            self.particles.handle_event(&event);
+            match event {
+                GameEvent::ItemPickup { player_id, .. } => {
+                    if let Some(local) = self.players.iter_mut().find(|l| l.player.entity_id == player_id) {
+                        local.effects.trigger_pickup();
+                    }
+                }
+                // Every local player feels it, from where they stand
+                GameEvent::Explosion { x, y, radius } => {
+                    for local in self.players.iter_mut() {
+                        local.effects.trigger_explosion((x, y), radius, &local.player.transform);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.particles.update(delta_time, &self.level);
+
+        // Frame limiting
+        let target_duration = Duration::from_secs_f64(1.0 / self.target_fps as f64);
+        let elapsed = self.time_of_last_loop.elapsed();
+        if elapsed < target_duration {
+            std::thread::sleep(target_duration - elapsed);
+        }
+
+        // Render every player in its own part of the screen
+        let mut stdout = std::io::stdout();
+        self.render_buffer.resize(self.term_size.0, self.term_size.1);
+        let viewports = Viewport::split(self.term_size, self.players.len());
+        for (local, viewport) in self.players.iter_mut().zip(viewports) {
+            local.effects.update(&local.player);
+            let projection = Projection::new(&self.config, viewport);
+            draw(&self.world, &local.player, &self.level, &self.particles, &local.effects, &projection, &mut self.render_buffer)?;
+        }
+        self.render_buffer.flush(&mut stdout)?;
+        stdout.flush()?;
+
+        Ok(false)
+    }
+}
@@ -4,15 +4,22 @@ use matchbox_socket::WebRtcSocket;
 use futures::future::BoxFuture;
 use crate::common::world::World;
 use crate::common::physics::Physics;
+use crate::common::events::GameEvent;
+use crate::common::ai::Ai;
+use crate::common::pathfinding::Navigation;
+use crate::modes::controls;
 use crate::common::level::Level;
 use crate::network::connection::setup_server;
 use crate::input::InputManager;
 use crate::graphics::RenderBuffer;
 use renet::ServerEvent;
-use crate::common::protocol::{ClientMessage, ServerMessage, PlayerInput};
+use crate::common::data::definitions_hash;
+use crate::common::protocol::{ClientMessage, ServerMessage, PlayerInput, Inventory};
 use crate::network::connection::PROTOCOL_ID;
 
 use crate::graphics::draw;
+use crate::graphics::{ParticleSystem, ScreenEffects, Projection, Viewport};
+use crate::config::Config;
 use crossterm::terminal;
 
 use crate::player::Player;
@@ -29,25 +36,39 @@ pub struct HostGame {
     pub player_id: u32,
     pub time_of_last_loop: std::time::Instant,
     pub client_map: std::collections::HashMap<ClientId, u32>,
+    // Ammo and weapons of each client's player, as last reported
+    pub inventories: std::collections::HashMap<u32, Inventory>,
+    pub particles: ParticleSystem,
+    pub navigation: Navigation,
+    pub effects: ScreenEffects,
+    pub config: Config,
 }
 
 impl HostGame {
-    pub fn new(level: Level) -> Result<Self> {
+    pub fn new(level: Level, config: Config) -> Result<Self> {
         let runtime = tokio::runtime::Runtime::new()?;
         let (server, socket, message_loop) = setup_server();
         
         // Spawn the message loop on the runtime
         runtime.spawn(message_loop);
         
+        // Clients get the skill with every snapshot
         let mut world = World::new();
+        world.skill = config.skill;
+        world.corpse_limit = config.corpse_limit.unwrap_or(level.corpse_limit);
         
         // Spawn local player
         let player_entity = crate::common::entity::Entity::new_player(0, 3.5, 3.5);
         let player_id = world.spawn_entity(player_entity);
 
+        // Enemies and items of the level, taken items come back
+        world.spawn_things(&level);
+        world.respawn_pickups = true;
+
         let (w, h) = terminal::size()?;
         let render_buffer = RenderBuffer::new(w, h);
-        let player = Player::new()?;
+        let mut player = Player::new()?;
+        player.entity_id = player_id;
         
         Ok(Self {
             server,
@@ -61,6 +82,11 @@ impl HostGame {
             player_id,
             time_of_last_loop: std::time::Instant::now(),
             client_map: std::collections::HashMap::new(),
+            inventories: std::collections::HashMap::new(),
+            particles: ParticleSystem::new(),
+            navigation: Navigation::new(),
+            effects: ScreenEffects::new(),
+            config,
         })
     }
 }
@@ -83,10 +109,14 @@ impl GameMode for HostGame {
                     // Spawn player for client
                     let player_id = self.world.spawn_entity(crate::common::entity::Entity::new_player(0, 3.5, 3.5));
                     self.client_map.insert(client_id, player_id);
+                    if let Ok(data) = bincode::serialize(&ServerMessage::Welcome { player_id, data_hash: definitions_hash() }) {
+                        self.server.send_message(client_id, 0, data);
+                    }
                 }
                 ServerEvent::ClientDisconnected { client_id, reason } => {
                     println!("Client {} disconnected: {:?}", client_id, reason);
                     if let Some(player_id) = self.client_map.remove(&client_id) {
+                        self.inventories.remove(&player_id);
                         self.world.remove_entity(player_id);
                     }
                 }
@@ -99,9 +129,21 @@ impl GameMode for HostGame {
                     match msg {
                         ClientMessage::Input(input) => {
                             if let Some(&player_id) = self.client_map.get(&client_id) {
+                                self.inventories.insert(player_id, input.inventory.clone());
+                                // Dead players can only ask to respawn, their client waits like
+                                // the local modes do (Player::wants_respawn)
+                                if let Some(entity) = self.world.get_entity(player_id) {
+                                    if entity.health <= 0 {
+                                        if input.respawn {
+                                            self.world.respawn_player(player_id, 3.5, 3.5);
+                                        }
+                                        continue;
+                                    }
+                                }
+
                                 // Apply input to client's player entity
                                 let move_speed = if input.move_forward || input.move_backward || input.strafe_left || input.strafe_right {
-                                     crate::common::entity::PLAYER_SPEED 
+                                     crate::common::entity::PLAYER_SPEED * self.world.get_entity(player_id).map_or(1.0, |e| e.speed_scale())
                                 } else { 0.0 };
                                 let rot_speed = crate::common::entity::PLAYER_ROTATION_SPEED;
 
@@ -137,47 +179,35 @@ impl GameMode for HostGame {
         let delta_time = now.duration_since(self.time_of_last_loop).as_secs_f64();
         self.time_of_last_loop = now;
 
-        // Local Player Movement
-        let mut move_speed = if input_manager.is_active(crate::input::Action::Sprint) { 
-            crate::common::entity::PLAYER_SPEED * 2.0 
-        } else { 
-            crate::common::entity::PLAYER_SPEED 
-        };
-        
-        let mut rot_speed = crate::common::entity::PLAYER_ROTATION_SPEED;
-
-        // Apply weapon weight penalty
-        let penalty = self.player.get_current_weapon().movement_penalty;
-        move_speed *= 1.0 - penalty;
-        rot_speed *= 1.0 - (penalty * 0.5);
-
-        if input_manager.is_active(crate::input::Action::MoveForward) {
-            Physics::move_entity_forward(&mut self.world, self.player_id, move_speed * delta_time, &self.level);
-        }
-        if input_manager.is_active(crate::input::Action::MoveBackward) {
-            Physics::move_entity_forward(&mut self.world, self.player_id, -move_speed * delta_time, &self.level);
-        }
-        if input_manager.is_active(crate::input::Action::RotateLeft) {
-            Physics::rotate_entity(&mut self.world, self.player_id, -rot_speed * delta_time);
-        }
-        if input_manager.is_active(crate::input::Action::RotateRight) {
-            Physics::rotate_entity(&mut self.world, self.player_id, rot_speed * delta_time);
-        }
-        if input_manager.is_active(crate::input::Action::StrafeLeft) {
-            Physics::strafe_entity(&mut self.world, self.player_id, -move_speed * delta_time, &self.level);
-        }
-        if input_manager.is_active(crate::input::Action::StrafeRight) {
-            Physics::strafe_entity(&mut self.world, self.player_id, move_speed * delta_time, &self.level);
+        // Local player movement, weapons and sync
+        if controls::update_local_player(&mut self.world, &self.level, &mut self.player, |action| input_manager.is_active(action), delta_time) {
+            self.world.respawn_player(self.player_id, 3.5, 3.5);
+            self.player.respawn(true);
         }
-        
-        // Sync player struct with entity (for rendering)
-        if let Some(entity) = self.world.get_entity(self.player_id) {
-            self.player.transform = entity.transform;
-            self.player.health = entity.health as u32;
+
+        // Dead clients watch their killer until they ask to respawn
+        for &player_id in self.client_map.values() {
+            if self.world.get_entity(player_id).is_some_and(|e| e.health <= 0) {
+                controls::death_camera(&mut self.world, player_id, delta_time);
+            }
         }
+        self.player.animation_update();
+
+        // Enemies think before the world moves
+        self.navigation.update(&self.world, &self.level);
+        Ai::update(&mut self.world, &self.level, &self.navigation, delta_time);
 
         // Run physics
+        // Kills are counted from the events, each player gets its own
         Physics::update(&mut self.world, delta_time, &self.level);
+        if self.level.update_gates(self.world.gates_open, &self.world.occupied_cells()) {
+            self.navigation = Navigation::new();
+        }
+
+        for (player_id, pickup_id) in Physics::touched_pickups(&self.world) {
+            let local = (player_id == self.player_id).then_some(&mut self.player);
+            controls::pick_up(&mut self.world, player_id, pickup_id, local, self.inventories.get(&player_id));
+        }
 
         // Broadcast state
         let snapshot = ServerMessage::WorldSnapshot(self.world.clone());
@@ -185,6 +215,21 @@ impl GameMode for HostGame {
             self.server.broadcast_message(0, data);
         }
 
+        // Forward gameplay events so clients can spawn their own particles
+        for event in self.world.drain_events() {
+            self.particles.handle_event(&event);
+            match event {
+                GameEvent::ItemPickup { player_id, .. } if player_id == self.player_id => self.effects.trigger_pickup(),
+                GameEvent::Explosion { x, y, radius } => self.effects.trigger_explosion((x, y), radius, &self.player.transform),
+                GameEvent::EnemyKilled { killer } if killer == self.player_id => self.player.kills += 1,
+                _ => {}
+            }
+            if let Ok(data) = bincode::serialize(&ServerMessage::Event(event)) {
+                self.server.broadcast_message(0, data);
+            }
+        }
+        self.particles.update(delta_time, &self.level);
+
         // Render
         let mut stdout = std::io::stdout();
         self.term_size = terminal::size()?;
@@ -192,7 +237,9 @@ impl GameMode for HostGame {
              self.render_buffer = RenderBuffer::new(self.term_size.0, self.term_size.1);
         }
         
-        draw(&self.world, &self.player, &self.level, self.term_size, &mut self.render_buffer)?;
+        self.effects.update(&self.player);
+        let projection = Projection::new(&self.config, Viewport::full(self.term_size));
+        draw(&self.world, &self.player, &self.level, &self.particles, &self.effects, &projection, &mut self.render_buffer)?;
         self.render_buffer.flush(&mut stdout)?;
         
         // Frame limiting
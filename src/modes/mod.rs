@@ -3,6 +3,11 @@ pub use self::gamemode::GameMode;
 pub mod solo;
 pub mod host;
 pub mod client;
+pub mod coop;
+pub mod survival;
+pub mod controls;
 pub use self::solo::SoloGame;
 pub use self::host::HostGame;
-pub use self::client::ClientGame;
\ No newline at end of file
+pub use self::client::ClientGame;
+pub use self::coop::CoopGame;
+pub use self::survival::SurvivalGame;
\ No newline at end of file
@@ -7,12 +7,18 @@ use crossterm::{
 use crate::level::Level;
 use crate::player::Player;
 use crate::world::World;
-use crate::entity::{Entity, EntityType, SpriteType};
+use crate::entity::{Entity, EntityType};
 use crate::weapon::Weapon;
 use crate::graphics::RenderBuffer;
 use crate::graphics::draw;
+use crate::graphics::{ParticleSystem, ScreenEffects, Projection, Viewport};
+use crate::config::Config;
 use crate::input::{InputManager, Action};
 use crate::common::physics::Physics;
+use crate::common::events::GameEvent;
+use crate::common::ai::Ai;
+use crate::common::pathfinding::Navigation;
+use crate::modes::controls;
 
 pub struct SoloGame {
     pub time_of_launch: Instant,
@@ -25,29 +31,32 @@ pub struct SoloGame {
     pub target_fps: u32,
     pub fps_toggle_cooldown: Instant,
     pub render_buffer: RenderBuffer,
+    pub particles: ParticleSystem,
+    pub navigation: Navigation,
+    pub effects: ScreenEffects,
+    pub config: Config,
 }
 
 impl SoloGame {
-    pub fn new(level: Level) -> Result<Self> {
+    pub fn new(level: Level, config: Config) -> Result<Self> {
         // Initialize render buffer
         let mut term_size = terminal::size()?;
         let mut render_buffer = RenderBuffer::new(term_size.0, term_size.1);
 
         let mut world = World::new();
+        world.skill = config.skill;
+        world.corpse_limit = config.corpse_limit.unwrap_or(level.corpse_limit);
         
         // Create player entity
         let player_entity = Entity::new_player(0, 3.5, 3.5);
         let player_id = world.spawn_entity(player_entity);
         
         // Create player struct
-        let player = Player::new()?;
+        let mut player = Player::new()?;
+        player.entity_id = player_id;
 
-        // Spawn some enemies
-        let imp = Entity::new_enemy(0, 10.5, 10.5, SpriteType::EnemyImp);
-        world.spawn_entity(imp);
-        
-        let demon = Entity::new_enemy(0, 7.5, 7.5, SpriteType::EnemyDemon);
-        world.spawn_entity(demon);
+        // Enemies and items of the level
+        world.spawn_things(&level);
 
         Ok(Self {
         time_of_launch: Instant::now(),
@@ -60,6 +69,10 @@ impl SoloGame {
         target_fps: 30,
         fps_toggle_cooldown: Instant::now(),
         render_buffer,
+        particles: ParticleSystem::new(),
+        navigation: Navigation::new(),
+        effects: ScreenEffects::new(),
+        config,
         })
     }
 }
@@ -83,100 +96,39 @@ impl GameMode for SoloGame {
         let delta_time = now.duration_since(self.time_of_last_loop).as_secs_f64();
         self.time_of_last_loop = now;
 
-        // Player Movement
-        let move_speed = if input_manager.is_active(Action::Sprint) { 
-            crate::common::entity::PLAYER_SPEED * 2.0 
-        } else { 
-            crate::common::entity::PLAYER_SPEED 
-        };
-        
-        let rot_speed = crate::common::entity::PLAYER_ROTATION_SPEED;
-
-        // Player Movement
-        let mut move_speed = if input_manager.is_active(Action::Sprint) { 
-            crate::common::entity::PLAYER_SPEED * 2.0 
-        } else { 
-            crate::common::entity::PLAYER_SPEED 
-        };
-        
-        let mut rot_speed = crate::common::entity::PLAYER_ROTATION_SPEED;
+        // Movement, weapons and player sync
+        if controls::update_local_player(&mut self.world, &self.level, &mut self.player, |action| input_manager.is_active(action), delta_time) {
+            // Solo starts over : fresh level, loadout and kill count
+            self.world.restart(&self.level);
+            self.world.respawn_player(self.player_id, 3.5, 3.5);
+            self.player.respawn(false);
+        }
 
-        // Apply weapon weight penalty
-        let penalty = self.player.get_current_weapon().movement_penalty;
-        move_speed *= 1.0 - penalty;
-        rot_speed *= 1.0 - (penalty * 0.5); // Less penalty on rotation
+        // Enemies think before the world moves
+        self.navigation.update(&self.world, &self.level);
+        Ai::update(&mut self.world, &self.level, &self.navigation, delta_time);
 
-        if input_manager.is_active(Action::MoveForward) {
-            Physics::move_entity_forward(&mut self.world, self.player_id, move_speed * delta_time, &self.level);
-        }
-        if input_manager.is_active(Action::MoveBackward) {
-            Physics::move_entity_forward(&mut self.world, self.player_id, -move_speed * delta_time, &self.level);
-        }
-        if input_manager.is_active(Action::RotateLeft) {
-            Physics::rotate_entity(&mut self.world, self.player_id, -rot_speed * delta_time);
-        }
-        if input_manager.is_active(Action::RotateRight) {
-            Physics::rotate_entity(&mut self.world, self.player_id, rot_speed * delta_time);
-        }
-        if input_manager.is_active(Action::StrafeLeft) {
-            Physics::strafe_entity(&mut self.world, self.player_id, -move_speed * delta_time, &self.level);
-        }
-        if input_manager.is_active(Action::StrafeRight) {
-            Physics::strafe_entity(&mut self.world, self.player_id, move_speed * delta_time, &self.level);
+        // Update world physics
+        let kills = Physics::update(&mut self.world, delta_time, &self.level);
+        self.player.kills += kills;
+        if self.level.update_gates(self.world.gates_open, &self.world.occupied_cells()) {
+            self.navigation = Navigation::new();
         }
-        
-        // Sync player struct with entity (for rendering)
-        if let Some(entity) = self.world.get_entity(self.player_id) {
-            self.player.transform = entity.transform;
-            self.player.health = entity.health as u32;
+
+        for (player_id, pickup_id) in Physics::touched_pickups(&self.world) {
+            controls::pick_up(&mut self.world, player_id, pickup_id, Some(&mut self.player), None);
         }
 
-        // Weapon handling
-        if input_manager.is_active(Action::Shoot) {
-            if self.player.fire() {
-                // Spawn projectile(s)
-                let weapon = self.player.get_current_weapon();
-                let count = weapon.projectile_count;
-                let spread = weapon.spread;
-                let damage = weapon.damage;
-                let range = weapon.range;
-                
-                // Determine sprite type based on weapon
-                let sprite_type = match weapon.weapon_type {
-                    crate::weapon::WeaponType::Pistol => crate::entity::SpriteType::ProjectilePistol,
-                    crate::weapon::WeaponType::Shotgun => crate::entity::SpriteType::ProjectileShotgun,
-                    crate::weapon::WeaponType::Gatling => crate::entity::SpriteType::ProjectileGatling,
-                };
-
-                let radians = self.player.transform.angle.to_radians();
-                let gun_offset = 0.5; // Distance from player center to gun barrel
-                let spawn_x = self.player.transform.x + radians.cos() * gun_offset;
-                let spawn_y = self.player.transform.y + radians.sin() * gun_offset;
-                let angle = self.player.transform.angle;
-            
-                for i in 0..count {
-                    let angle_offset = if count > 1 {
-                        (i as f64 - (count as f64 - 1.0) / 2.0) * spread
-                    } else {
-                        0.0
-                    };
-                    
-                    self.world.spawn_projectile(spawn_x, spawn_y, angle + angle_offset, damage, range, sprite_type);
-                }
+        // Turn gameplay events into particles
+        for event in self.world.drain_events() {
+            self.particles.handle_event(&event);
+            match event {
+                GameEvent::ItemPickup { player_id, .. } if player_id == self.player_id => self.effects.trigger_pickup(),
+                GameEvent::Explosion { x, y, radius } => self.effects.trigger_explosion((x, y), radius, &self.player.transform),
+                _ => {}
             }
         }
-        if input_manager.is_active(Action::Reload) {
-            self.player.reload();
-        }
-        
-        // Weapon switching
-        if input_manager.is_active(Action::SwitchWeapon1) { self.player.switch_weapon(0); }
-        if input_manager.is_active(Action::SwitchWeapon2) { self.player.switch_weapon(1); }
-        if input_manager.is_active(Action::SwitchWeapon3) { self.player.switch_weapon(2); }
-
-        // Update world physics
-        let kills = Physics::update(&mut self.world, delta_time, &self.level);
-        self.player.kills += kills;
+        self.particles.update(delta_time, &self.level);
         
         // Update player animation
         self.player.animation_update();
@@ -190,7 +142,10 @@ impl GameMode for SoloGame {
 
         // Render 3D raycasting view
         let mut stdout = std::io::stdout();
-        draw(&self.world, &self.player, &self.level, self.term_size, &mut self.render_buffer)?;
+        self.render_buffer.resize(self.term_size.0, self.term_size.1);
+        self.effects.update(&self.player);
+        let projection = Projection::new(&self.config, Viewport::full(self.term_size));
+        draw(&self.world, &self.player, &self.level, &self.particles, &self.effects, &projection, &mut self.render_buffer)?;
         self.render_buffer.flush(&mut stdout)?;
 
         write.flush()?;
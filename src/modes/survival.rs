@@ -0,0 +1,185 @@
+/* Survival : the level's monsters are left out, waves of them come in
+instead (see waves.rs). Items are restocked between waves, and dying ends the
+run with its score added to the high score table. */
+
+use std::io::{Write, Result};
+use std::time::{Duration, Instant};
+use crossterm::terminal;
+use crate::level::Level;
+use crate::player::Player;
+use crate::world::World;
+use crate::entity::Entity;
+use crate::graphics::RenderBuffer;
+use crate::graphics::draw;
+use crate::graphics::rendering::{draw_wave_hud, draw_high_scores};
+use crate::graphics::{ParticleSystem, ScreenEffects, Projection, Viewport};
+use crate::config::Config;
+use crate::input::{InputManager, Action};
+use crate::common::physics::Physics;
+use crate::common::events::GameEvent;
+use crate::common::ai::Ai;
+use crate::common::pathfinding::Navigation;
+use crate::common::waves::Waves;
+use crate::highscores::{HighScores, ScoreEntry};
+use crate::modes::controls;
+
+pub struct SurvivalGame {
+    pub time_of_last_loop: Instant,
+    pub player: Player,
+    pub player_id: u32,
+    pub world: World,
+    pub level: Level,
+    pub waves: Waves,
+    pub high_scores: HighScores,
+    // Set when the player died, with the rank the run got in the table
+    pub run_over: bool,
+    pub rank: Option<usize>,
+    pub term_size: (u16, u16),
+    pub target_fps: u32,
+    pub render_buffer: RenderBuffer,
+    pub particles: ParticleSystem,
+    pub navigation: Navigation,
+    pub effects: ScreenEffects,
+    pub config: Config,
+}
+
+impl SurvivalGame {
+    pub fn new(level: Level, config: Config) -> Result<Self> {
+        let term_size = terminal::size()?;
+        let render_buffer = RenderBuffer::new(term_size.0, term_size.1);
+
+        let mut world = World::new();
+        world.skill = config.skill;
+        world.corpse_limit = config.corpse_limit.unwrap_or(level.corpse_limit);
+        // Nightmare respawns would bring back monsters the waves already scored
+        world.respawn_monsters = false;
+
+        let player_id = world.spawn_entity(Entity::new_player(0, 3.5, 3.5));
+        let mut player = Player::new()?;
+        player.entity_id = player_id;
+
+        // Items only, the monsters come with the waves
+        world.restock_pickups(&level);
+
+        Ok(Self {
+            time_of_last_loop: Instant::now(),
+            player,
+            player_id,
+            world,
+            level,
+            waves: Waves::new(),
+            high_scores: HighScores::load()?,
+            run_over: false,
+            rank: None,
+            term_size,
+            target_fps: 30,
+            render_buffer,
+            particles: ParticleSystem::new(),
+            navigation: Navigation::new(),
+            effects: ScreenEffects::new(),
+            config,
+        })
+    }
+
+    // Put the run in the table. A table that can't be written is not worth
+    // ending the game over, the run still shows in this session
+    fn end_run(&mut self) {
+        self.rank = self.high_scores.insert(ScoreEntry {
+            score: self.waves.score,
+            wave: self.waves.wave,
+            kills: self.player.kills,
+            skill: self.world.skill.level(),
+        });
+        if self.rank.is_some() {
+            let _ = self.high_scores.save();
+        }
+        self.run_over = true;
+    }
+
+    // Fresh world and waves for the next run
+    fn start_run(&mut self) {
+        self.world.clear_level();
+        self.world.restock_pickups(&self.level);
+        self.world.respawn_player(self.player_id, 3.5, 3.5);
+        self.player.respawn(false);
+        self.waves = Waves::new();
+        self.run_over = false;
+        self.rank = None;
+    }
+}
+
+use crate::modes::gamemode::GameMode;
+
+impl GameMode for SurvivalGame {
+    fn update(&mut self, input_manager: &InputManager) -> Result<bool> {
+        if input_manager.is_active(Action::Quit) {
+            return Ok(true);
+        }
+
+        self.term_size = terminal::size()?;
+
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.time_of_last_loop).as_secs_f64();
+        self.time_of_last_loop = now;
+
+        if controls::update_local_player(&mut self.world, &self.level, &mut self.player, |action| input_manager.is_active(action), delta_time) {
+            self.start_run();
+        }
+        if self.player.is_dead() && !self.run_over {
+            self.end_run();
+        }
+
+        // The waves stop with the run
+        if !self.run_over {
+            let skip_break = input_manager.is_active(Action::NextWave);
+            if self.waves.update(&mut self.world, &self.level, skip_break, delta_time) {
+                self.world.restock_pickups(&self.level);
+            }
+        }
+
+        self.navigation.update(&self.world, &self.level);
+        Ai::update(&mut self.world, &self.level, &self.navigation, delta_time);
+
+        self.player.kills += Physics::update(&mut self.world, delta_time, &self.level);
+        if self.level.update_gates(self.world.gates_open, &self.world.occupied_cells()) {
+            self.navigation = Navigation::new();
+        }
+
+        for (player_id, pickup_id) in Physics::touched_pickups(&self.world) {
+            controls::pick_up(&mut self.world, player_id, pickup_id, Some(&mut self.player), None);
+        }
+
+        for event in self.world.drain_events() {
+            self.particles.handle_event(&event);
+            match event {
+                GameEvent::ItemPickup { player_id, .. } if player_id == self.player_id => self.effects.trigger_pickup(),
+                GameEvent::Explosion { x, y, radius } => self.effects.trigger_explosion((x, y), radius, &self.player.transform),
+                _ => {}
+            }
+        }
+        self.particles.update(delta_time, &self.level);
+        self.player.animation_update();
+
+        // Frame limiting
+        let target_duration = Duration::from_secs_f64(1.0 / self.target_fps as f64);
+        let elapsed = self.time_of_last_loop.elapsed();
+        if elapsed < target_duration {
+            std::thread::sleep(target_duration - elapsed);
+        }
+
+        let mut stdout = std::io::stdout();
+        self.render_buffer.resize(self.term_size.0, self.term_size.1);
+        self.effects.update(&self.player);
+        let viewport = Viewport::full(self.term_size);
+        let projection = Projection::new(&self.config, viewport);
+        draw(&self.world, &self.player, &self.level, &self.particles, &self.effects, &projection, &mut self.render_buffer)?;
+        draw_wave_hud(&self.waves, &viewport, &mut self.render_buffer);
+        if self.run_over {
+            draw_high_scores(&self.high_scores, self.rank, &viewport, &mut self.render_buffer);
+        }
+        self.render_buffer.flush(&mut stdout)?;
+        stdout.flush()?;
+
+        Ok(false)
+    }
+}
@@ -1,20 +1,83 @@
-use crate::weapon::Weapon;
+use crate::common::entity::{ArmorTier, Entity};
+use crate::common::pickups::PickupKind;
+use crate::common::powerups::Effect;
+use crate::common::protocol::Inventory;
+use crate::weapon::{AmmoType, Weapon};
+use std::collections::HashMap;
 use std::io::Result;
 
 use std::time::{Duration, Instant};
 
+// Ammo carried in reserve, shared by every weapon of the same type.
+// Weapons without ammo type never run out
+#[derive(Debug, Clone)]
+pub struct AmmoPool {
+    pub amounts: HashMap<AmmoType, u32>,
+}
+
+impl AmmoPool {
+    pub fn starting() -> Self {
+        Self {
+            amounts: crate::weapon::ammo_definitions().iter()
+                .map(|def| (def.ammo_type, def.start.min(def.max)))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, ammo_type: AmmoType) -> u32 {
+        self.amounts.get(&ammo_type).copied().unwrap_or(0)
+    }
+
+    pub fn max(ammo_type: AmmoType) -> u32 {
+        crate::weapon::ammo_definitions().iter()
+            .find(|def| def.ammo_type == ammo_type)
+            .map_or(0, |def| def.max)
+    }
+
+    // Add ammo up to the maximum, returns how much was actually added
+    pub fn add(&mut self, ammo_type: AmmoType, amount: u32) -> u32 {
+        let current = self.get(ammo_type);
+        let added = amount.min(Self::max(ammo_type).saturating_sub(current));
+        self.amounts.insert(ammo_type, current + added);
+        added
+    }
+
+    // Take up to `amount`, returns how much was available
+    pub fn take(&mut self, ammo_type: AmmoType, amount: u32) -> u32 {
+        if ammo_type == AmmoType::None {
+            return amount;
+        }
+        let current = self.get(ammo_type);
+        let taken = amount.min(current);
+        self.amounts.insert(ammo_type, current - taken);
+        taken
+    }
+}
+
 pub struct Player {
     pub entity_id: u32,
     pub max_speed: f64,
     pub max_rotation_speed: f64,
     pub health: u32,
+    // Copied from the entity, like health
+    pub armor: u32,
+    pub armor_tier: ArmorTier,
+    // Running power-ups, copied from the entity too
+    pub effects: Vec<Effect>,
     pub weapons: Vec<Weapon>,
+    pub ammo: AmmoPool,
     pub current_weapon: usize,
     pub switch_cooldown_expiry: Instant,
     pub kills: u32,
     pub transform: crate::entity::Transform,
+    pub death_time: Option<Instant>,
+    // The respawn key was up once respawning got allowed
+    pub respawn_released: bool,
 }
 
+// Time to watch the death camera before respawning is allowed
+pub const RESPAWN_DELAY: Duration = Duration::from_secs(1);
+
 impl Player {
     pub fn new() -> Result<Self> {
         Ok(Self {
@@ -22,14 +85,77 @@ impl Player {
             max_speed: 4.0,
             max_rotation_speed: 50.0,
             health: 100,
-            weapons: vec![Weapon::new_pistol(), Weapon::new_shotgun(), Weapon::new_gatling(), Weapon::new_shotgun()],
-            current_weapon: 0,
+            armor: 0,
+            armor_tier: ArmorTier::None,
+            effects: Vec::new(),
+            weapons: Self::starting_weapons(),
+            ammo: AmmoPool::starting(),
+            current_weapon: Self::starting_weapon_index(),
             switch_cooldown_expiry: Instant::now(),
             kills: 0,
             transform: crate::entity::Transform::new(3.5, 3.5, 0.0),
+            death_time: None,
+            respawn_released: false,
         })
     }
 
+    // Weapons listed as starting in the weapon definitions, in slot order
+    pub fn starting_weapons() -> Vec<Weapon> {
+        crate::weapon::definitions().iter()
+            .filter(|def| def.starting)
+            .map(Weapon::from_def)
+            .collect()
+    }
+
+    // Health, armor and other stats kept by the entity
+    pub fn sync_with(&mut self, entity: &Entity) {
+        self.health = entity.health.max(0) as u32;
+        self.armor = entity.armor.max(0) as u32;
+        self.armor_tier = entity.armor_tier;
+        self.effects.clone_from(&entity.effects);
+    }
+
+    // Start with the best weapon of the loadout rather than the fists
+    fn starting_weapon_index() -> usize {
+        Self::starting_weapons().len().saturating_sub(1)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health == 0
+    }
+
+    pub fn can_respawn(&self) -> bool {
+        self.death_time.is_some_and(|t| t.elapsed() >= RESPAWN_DELAY)
+    }
+
+    // Respawn on a fresh press of the respawn key. It is often the fire key
+    // too, holding fire through the death must not respawn right away
+    pub fn wants_respawn(&mut self, pressed: bool) -> bool {
+        if !self.can_respawn() {
+            return false;
+        }
+        if !pressed {
+            self.respawn_released = true;
+        }
+        pressed && self.respawn_released
+    }
+
+    // Back to the starting loadout, kills are only kept in multiplayer modes
+    pub fn respawn(&mut self, keep_kills: bool) {
+        self.health = crate::entity::PLAYER_HEALTH as u32;
+        self.armor = 0;
+        self.armor_tier = ArmorTier::None;
+        self.effects.clear();
+        self.weapons = Self::starting_weapons();
+        self.ammo = AmmoPool::starting();
+        self.current_weapon = Self::starting_weapon_index();
+        self.death_time = None;
+        self.respawn_released = false;
+        if !keep_kills {
+            self.kills = 0;
+        }
+    }
+
     pub fn get_current_weapon(&self) -> &Weapon {
         &self.weapons[self.current_weapon]
     }
@@ -38,22 +164,86 @@ impl Player {
         if Instant::now() < self.switch_cooldown_expiry {
             return false;
         }
+        if self.weapons[self.current_weapon].ammo == 0 {
+            // Empty magazine : reload instead
+            self.reload();
+            return false;
+        }
         self.weapons[self.current_weapon].fire()
     }
 
+    // Ammo left in reserve for the current weapon
+    pub fn reserve(&self) -> u32 {
+        self.ammo.get(self.get_current_weapon().ammo_type)
+    }
+
     pub fn reload(&mut self) {
         if Instant::now() < self.switch_cooldown_expiry {
             return;
         }
-        self.weapons[self.current_weapon].reload()
+        let weapon = &mut self.weapons[self.current_weapon];
+        if weapon.ammo_type != AmmoType::None && self.ammo.get(weapon.ammo_type) == 0 {
+            return;
+        }
+        weapon.reload()
     }
 
     pub fn animation_update(&mut self) {
-        self.weapons[self.current_weapon].update();
+        let weapon = &mut self.weapons[self.current_weapon];
+        if weapon.update() {
+            // Fill the magazine from the reserve
+            let needed = weapon.max_ammo - weapon.ammo;
+            weapon.ammo += self.ammo.take(weapon.ammo_type, needed);
+        }
+    }
+
+    // Ammo and weapons as reported to the host in network games
+    pub fn inventory(&self) -> Inventory {
+        Inventory {
+            full_ammo: [AmmoType::None, AmmoType::Bullets, AmmoType::Shells, AmmoType::Rockets, AmmoType::Cells]
+                .into_iter()
+                .filter(|&ammo_type| self.ammo.get(ammo_type) >= AmmoPool::max(ammo_type))
+                .collect(),
+            weapons: self.weapons.iter().map(|w| w.id.clone()).collect(),
+        }
     }
 
-    pub fn switch_weapon(&mut self, index: usize) {
-        if index < self.weapons.len() && index != self.current_weapon {
+    // Apply the ammo and weapons of an item, false if it was of no use
+    pub fn give(&mut self, kind: PickupKind) -> bool {
+        if let Some((ammo_type, amount)) = kind.ammo() {
+            return self.ammo.add(ammo_type, amount) > 0;
+        }
+        let PickupKind::Weapon(weapon) = kind else { return false };
+        let Some(def) = crate::weapon::definitions().get(weapon) else { return false };
+
+        // A weapon already owned only gives its ammo
+        let ammo = self.ammo.add(def.ammo_type, def.magazine);
+        if self.weapons.iter().any(|w| w.id == def.id) {
+            return ammo > 0;
+        }
+        let index = self.weapons.iter().position(|w| w.slot > def.slot).unwrap_or(self.weapons.len());
+        self.weapons.insert(index, Weapon::from_def(def));
+        if index <= self.current_weapon {
+            self.current_weapon += 1;
+        }
+        true
+    }
+
+    // Switch to a weapon of the given slot, if any is owned. Asking for the
+    // slot of the current weapon cycles through the weapons sharing it
+    pub fn switch_weapon(&mut self, slot: usize) {
+        if Instant::now() < self.switch_cooldown_expiry {
+            return;
+        }
+        let in_slot: Vec<usize> = (0..self.weapons.len()).filter(|&i| self.weapons[i].slot == slot).collect();
+        let index = match in_slot.iter().position(|&i| i == self.current_weapon) {
+            Some(current) => in_slot[(current + 1) % in_slot.len()],
+            None => match in_slot.first() {
+                Some(&first) => first,
+                None => return,
+            },
+        };
+        if index != self.current_weapon {
             self.current_weapon = index;
             self.switch_cooldown_expiry = Instant::now() + Duration::from_millis(500);
             // Reset state of previous weapon if needed? No, they keep state.
@@ -62,11 +252,78 @@ impl Player {
         }
     }
 
-    pub fn take_damage(&mut self, amount: u32) {
-        self.health = self.health.saturating_sub(amount);
-    }
-
     pub fn is_firing(&self) -> bool {
         self.weapons[self.current_weapon].state == crate::weapon::WeaponState::Firing
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ammo_pool_is_capped_and_never_negative() {
+        let mut pool = AmmoPool { amounts: HashMap::new() };
+        let max = AmmoPool::max(AmmoType::Shells);
+        assert_eq!(pool.add(AmmoType::Shells, max + 10), max);
+        assert_eq!(pool.take(AmmoType::Shells, 4), 4);
+        assert_eq!(pool.take(AmmoType::Shells, max), max - 4);
+        assert_eq!(pool.get(AmmoType::Shells), 0);
+        assert_eq!(pool.take(AmmoType::None, 3), 3);
+    }
+
+    #[test]
+    fn weapon_pickup_keeps_slot_order_and_current_weapon() {
+        let mut player = Player::new().unwrap();
+        let current = player.get_current_weapon().id.clone();
+        let defs = crate::weapon::definitions();
+        for index in (0..defs.len()).rev() {
+            player.give(PickupKind::Weapon(index));
+        }
+        assert_eq!(player.weapons.len(), defs.len());
+        assert_eq!(player.get_current_weapon().id, current);
+        assert!(player.weapons.windows(2).all(|pair| pair[0].slot <= pair[1].slot));
+    }
+
+    #[test]
+    fn switching_to_the_current_slot_cycles_through_it() {
+        let mut player = Player::new().unwrap();
+        player.give(PickupKind::Weapon(crate::weapon::index_of("chainsaw").unwrap()));
+        player.switch_weapon(1);
+        assert_eq!(player.get_current_weapon().id, "fist");
+        player.switch_cooldown_expiry = Instant::now();
+        player.switch_weapon(1);
+        assert_eq!(player.get_current_weapon().id, "chainsaw");
+        player.switch_cooldown_expiry = Instant::now();
+        player.switch_weapon(1);
+        assert_eq!(player.get_current_weapon().id, "fist");
+    }
+
+    #[test]
+    fn reported_inventory_agrees_with_give() {
+        let mut player = Player::new().unwrap();
+        player.ammo.add(AmmoType::Shells, AmmoPool::max(AmmoType::Shells));
+        let ammo = [PickupKind::AmmoClip, PickupKind::AmmoShells, PickupKind::AmmoRockets, PickupKind::AmmoCells];
+        let weapons = (0..crate::weapon::definitions().len()).map(PickupKind::Weapon);
+        // Twice, so the weapons are owned the second time
+        for kind in ammo.into_iter().chain(weapons.clone()).chain(weapons) {
+            assert_eq!(player.inventory().wants(kind), player.give(kind), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn respawn_needs_a_fresh_press() {
+        let mut player = Player::new().unwrap();
+        player.health = 0;
+        assert!(!player.wants_respawn(true));
+        player.death_time = Some(Instant::now() - RESPAWN_DELAY);
+        // Still holding fire from before the death
+        assert!(!player.wants_respawn(true));
+        assert!(!player.wants_respawn(false));
+        assert!(player.wants_respawn(true));
+
+        player.respawn(false);
+        player.death_time = Some(Instant::now() - RESPAWN_DELAY);
+        assert!(!player.wants_respawn(true));
+    }
+}
@@ -1,11 +1,33 @@
+use std::io::{Error, ErrorKind, Result};
 use std::time::{Duration, Instant};
 use crossterm::style::Color;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum WeaponType {
-    Pistol,
-    Shotgun,
-    Gatling,
+use crate::common::data::DataFile;
+use crate::entity::{SpriteType, Splash};
+
+// ASCII animation sets a definition can refer to
+const SPRITE_SETS: [&str; 6] = ["fist", "chainsaw", "pistol", "shotgun", "gatling", "rocket"];
+
+pub static WEAPONS: DataFile<WeaponFile> = DataFile::new("data/weapons.toml", include_str!("../data/weapons.toml"), parse_definitions);
+
+// How a shot reaches its target
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FireMode {
+    Hitscan,    // Instant ray, hits the first wall or entity on the line
+    Projectile, // Spawns projectile entities that fly through the world
+    Melee,      // Short range hit in front of the player
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmmoType {
+    None,
+    Bullets,
+    Shells,
+    Rockets,
+    Cells,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,8 +38,139 @@ pub enum WeaponState {
     Reloading,
 }
 
+// Names of the sounds to play, for the audio backend
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WeaponSounds {
+    pub fire: Option<String>,
+    pub reload: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoundCue {
+    Fire,
+    Reload,
+}
+
+impl WeaponSounds {
+    pub fn cue(&self, cue: SoundCue) -> Option<&str> {
+        match cue {
+            SoundCue::Fire => self.fire.as_deref(),
+            SoundCue::Reload => self.reload.as_deref(),
+        }
+    }
+}
+
+// One entry of the weapons file, see data/weapons.toml for the fields
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeaponDef {
+    pub id: String,
+    pub name: String,
+    pub slot: usize,
+    #[serde(default)]
+    pub starting: bool,
+    pub fire_mode: FireMode,
+    pub ammo_type: AmmoType,
+    pub magazine: u32,
+    pub damage: i32,
+    pub range: f64,
+    #[serde(default)]
+    pub spread: f64,
+    #[serde(default = "default_pellets")]
+    pub pellets: u32,
+    #[serde(default = "default_melee_arc")]
+    pub melee_arc: f64,
+    pub frame_ms: u64,
+    pub reload_frames: usize,
+    #[serde(default)]
+    pub movement_penalty: f64,
+    pub sprite: String,
+    #[serde(default = "default_projectile_sprite")]
+    pub projectile_sprite: SpriteType,
+    #[serde(default)]
+    pub splash_radius: f64,
+    #[serde(default)]
+    pub knockback: f64,
+    #[serde(default)]
+    pub sounds: WeaponSounds,
+}
+
+fn default_pellets() -> u32 {
+    1
+}
+
+fn default_melee_arc() -> f64 {
+    60.0
+}
+
+fn default_projectile_sprite() -> SpriteType {
+    SpriteType::ProjectilePistol
+}
+
+// Capacity of one ammo pool
+#[derive(Debug, Clone, Deserialize)]
+pub struct AmmoDef {
+    #[serde(rename = "type")]
+    pub ammo_type: AmmoType,
+    pub max: u32,
+    #[serde(default)]
+    pub start: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WeaponFile {
+    pub weapon: Vec<WeaponDef>,
+    #[serde(default)]
+    pub ammo: Vec<AmmoDef>,
+}
+
+fn parse_definitions(text: &str, origin: &str) -> Result<WeaponFile> {
+    let invalid = |message: String| Error::new(ErrorKind::InvalidData, format!("{}: {}", origin, message));
+    let mut file = toml::from_str::<WeaponFile>(text).map_err(|e| invalid(e.to_string()))?;
+    for (index, def) in file.weapon.iter().enumerate() {
+        if file.weapon[..index].iter().any(|other| other.id == def.id) {
+            return Err(invalid(format!("weapon {} is defined twice", def.id)));
+        }
+        if !SPRITE_SETS.contains(&def.sprite.as_str()) {
+            return Err(invalid(format!("weapon {} uses unknown sprite {}", def.id, def.sprite)));
+        }
+        if def.magazine == 0 || def.pellets == 0 {
+            return Err(invalid(format!("weapon {} needs a magazine and at least one pellet", def.id)));
+        }
+        if def.splash_radius > 0.0 && def.fire_mode != FireMode::Projectile {
+            return Err(invalid(format!("weapon {} has a splash but no projectiles", def.id)));
+        }
+        if def.ammo_type != AmmoType::None && !file.ammo.iter().any(|ammo| ammo.ammo_type == def.ammo_type) {
+            return Err(invalid(format!("weapon {} uses ammo without a pool", def.id)));
+        }
+    }
+    file.weapon.sort_by_key(|def| def.slot);
+    Ok(file)
+}
+
+// Loaded definitions, sorted by slot
+pub fn definitions() -> &'static [WeaponDef] {
+    &WEAPONS.get().weapon
+}
+
+pub fn ammo_definitions() -> &'static [AmmoDef] {
+    &WEAPONS.get().ammo
+}
+
+// Position of a weapon in the definitions, how pickups refer to weapons
+pub fn index_of(id: &str) -> Option<usize> {
+    definitions().iter().position(|def| def.id == id)
+}
+
 pub struct Weapon {
-    pub weapon_type: WeaponType,
+    pub id: String,
+    pub name: String,
+    pub slot: usize,
+    pub fire_mode: FireMode,
+    pub ammo_type: AmmoType,
+    pub sprite: String,
+    pub projectile_sprite: SpriteType,
+    // Projectiles explode on impact when set
+    pub splash: Option<Splash>,
     pub state: WeaponState,
     pub animation_frame: usize,
     pub animation_timer: Instant,
@@ -28,68 +181,44 @@ pub struct Weapon {
     pub range: f64,
     pub spread: f64,
     pub projectile_count: u32,
+    pub melee_arc: f64,
     pub reload_frames: usize,
     pub movement_penalty: f64,
 }
 
 impl Weapon {
-    pub fn new_pistol() -> Self {
+    pub fn from_def(def: &WeaponDef) -> Self {
         Self {
-            weapon_type: WeaponType::Pistol,
+            id: def.id.clone(),
+            name: def.name.clone(),
+            slot: def.slot,
+            fire_mode: def.fire_mode,
+            ammo_type: def.ammo_type,
+            sprite: def.sprite.clone(),
+            projectile_sprite: def.projectile_sprite,
+            splash: (def.splash_radius > 0.0).then_some(Splash { radius: def.splash_radius, knockback: def.knockback }),
             state: WeaponState::Idle,
             animation_frame: 0,
             animation_timer: Instant::now(),
-            frame_duration: Duration::from_millis(100),
-            ammo: 8,
-            max_ammo: 8,
-            damage: 20,
-            range: 15.0,
-            spread: 0.0,
-            projectile_count: 1,
-            reload_frames: 3,
-            movement_penalty: 0.2, // 20% slow
-        }
-    }
-
-    pub fn new_shotgun() -> Self {
-        Self {
-            weapon_type: WeaponType::Shotgun,
-            state: WeaponState::Idle,
-            animation_frame: 0,
-            animation_timer: Instant::now(),
-            frame_duration: Duration::from_millis(250),
-            ammo: 6,
-            max_ammo: 6,
-            damage: 10,
-            range: 8.0,
-            spread: 5.0,
-            projectile_count: 5,
-            reload_frames: 4,
-            movement_penalty: 0.5, // 50% slow
-        }
-    }
-
-    pub fn new_gatling() -> Self {
-        Self {
-            weapon_type: WeaponType::Gatling,
-            state: WeaponState::Idle,
-            animation_frame: 0,
-            animation_timer: Instant::now(),
-            frame_duration: Duration::from_millis(50),
-            ammo: 100,
-            max_ammo: 100,
-            damage: 15,
-            range: 12.0,
-            spread: 2.0,
-            projectile_count: 1,
-            reload_frames: 5,
-            movement_penalty: 0.6, // 60% slow
+            frame_duration: Duration::from_millis(def.frame_ms),
+            ammo: def.magazine,
+            max_ammo: def.magazine,
+            damage: def.damage,
+            range: def.range,
+            spread: def.spread,
+            projectile_count: def.pellets,
+            melee_arc: def.melee_arc,
+            reload_frames: def.reload_frames,
+            movement_penalty: def.movement_penalty,
         }
     }
 
     pub fn fire(&mut self) -> bool {
         if self.ammo > 0 && self.state == WeaponState::Idle {
-            self.ammo -= 1;
+            // Weapons without ammo type never empty their magazine
+            if self.ammo_type != AmmoType::None {
+                self.ammo -= 1;
+            }
             self.state = WeaponState::Firing;
             self.animation_frame = 0;
             self.animation_timer = Instant::now();
@@ -107,7 +236,10 @@ impl Weapon {
         }
     }
 
-    pub fn update(&mut self) {
+    // Advance the animation, returns true when a reload just finished and the
+    // magazine should be filled from the owner's reserve
+    pub fn update(&mut self) -> bool {
+        let mut reloaded = false;
         if self.animation_timer.elapsed() >= self.frame_duration {
             match self.state {
                 WeaponState::Firing => {
@@ -127,31 +259,42 @@ impl Weapon {
                 WeaponState::Reloading => {
                     self.animation_frame += 1;
                     if self.animation_frame >= self.reload_frames {
-                        self.ammo = self.max_ammo;
                         self.state = WeaponState::Idle;
                         self.animation_frame = 0;
+                        reloaded = true;
                     }
                 }
                 WeaponState::Idle => {}
             }
             self.animation_timer = Instant::now();
         }
+        reloaded
     }
 
     pub fn get_current_sprite(&self) -> WeaponSprite {
-        match (self.weapon_type, self.state) {
-            (WeaponType::Pistol, WeaponState::Idle) => get_pistol_idle(),
-            (WeaponType::Pistol, WeaponState::Firing) => get_pistol_firing(),
-            (WeaponType::Pistol, WeaponState::Recoil) => get_pistol_recoil(),
-            (WeaponType::Pistol, WeaponState::Reloading) => get_pistol_reloading(),
-            (WeaponType::Shotgun, WeaponState::Idle) => get_shotgun_idle(),
-            (WeaponType::Shotgun, WeaponState::Firing) => get_shotgun_firing(),
-            (WeaponType::Shotgun, WeaponState::Recoil) => get_shotgun_recoil(),
-            (WeaponType::Shotgun, WeaponState::Reloading) => get_shotgun_reloading(),
-            (WeaponType::Gatling, WeaponState::Idle) => get_gatling_idle(),
-            (WeaponType::Gatling, WeaponState::Firing) => get_gatling_firing(),
-            (WeaponType::Gatling, WeaponState::Recoil) => get_gatling_recoil(),
-            (WeaponType::Gatling, WeaponState::Reloading) => get_gatling_reloading(),
+        match (self.sprite.as_str(), self.state) {
+            ("fist", WeaponState::Idle) => get_fist_idle(),
+            ("fist", WeaponState::Firing) => get_fist_punch(),
+            ("fist", _) => get_fist_recoil(),
+            ("chainsaw", WeaponState::Idle) => get_chainsaw_idle(),
+            ("chainsaw", WeaponState::Firing) => get_chainsaw_rip(),
+            ("chainsaw", _) => get_chainsaw_idle(),
+            ("shotgun", WeaponState::Idle) => get_shotgun_idle(),
+            ("shotgun", WeaponState::Firing) => get_shotgun_firing(),
+            ("shotgun", WeaponState::Recoil) => get_shotgun_recoil(),
+            ("shotgun", WeaponState::Reloading) => get_shotgun_reloading(),
+            ("gatling", WeaponState::Idle) => get_gatling_idle(),
+            ("gatling", WeaponState::Firing) => get_gatling_firing(),
+            ("gatling", WeaponState::Recoil) => get_gatling_recoil(),
+            ("gatling", WeaponState::Reloading) => get_gatling_reloading(),
+            ("rocket", WeaponState::Idle) => get_rocket_idle(),
+            ("rocket", WeaponState::Firing) => get_rocket_firing(),
+            ("rocket", WeaponState::Recoil) => get_rocket_recoil(),
+            ("rocket", WeaponState::Reloading) => get_rocket_reloading(),
+            (_, WeaponState::Idle) => get_pistol_idle(),
+            (_, WeaponState::Firing) => get_pistol_firing(),
+            (_, WeaponState::Recoil) => get_pistol_recoil(),
+            (_, WeaponState::Reloading) => get_pistol_reloading(),
         }
     }
 }
@@ -178,6 +321,125 @@ impl WeaponSprite {
     }
 }
 
+// ===== FIST SPRITES =====
+
+fn get_fist_idle() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "                    ",
+            "                    ",
+            "                    ",
+            "  ██████            ",
+            " ████████           ",
+            " ████████           ",
+            "  ██████            ",
+        ],
+        vec![
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:210,g:160,b:120}; 20], // Knuckles
+            vec![Color::Rgb{r:190,g:140,b:100}; 20], // Skin
+            vec![Color::Rgb{r:170,g:120,b:90}; 20],
+            vec![Color::Rgb{r:150,g:105,b:80}; 20],
+        ]
+    )
+}
+
+fn get_fist_punch() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "       ██████       ",
+            "      ████████      ",
+            "      ████████      ",
+            "       ██████       ",
+            "       ██████       ",
+            "      ████████      ",
+            "     ██████████     ",
+        ],
+        vec![
+            vec![Color::Rgb{r:230,g:180,b:140}; 20], // Knuckles forward
+            vec![Color::Rgb{r:210,g:160,b:120}; 20],
+            vec![Color::Rgb{r:190,g:140,b:100}; 20],
+            vec![Color::Rgb{r:170,g:120,b:90}; 20],  // Forearm
+            vec![Color::Rgb{r:160,g:115,b:85}; 20],
+            vec![Color::Rgb{r:150,g:105,b:80}; 20],
+            vec![Color::Rgb{r:140,g:100,b:75}; 20],
+        ]
+    )
+}
+
+fn get_fist_recoil() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "                    ",
+            "                    ",
+            "                    ",
+            "                    ",
+            "    ██████          ",
+            "   ████████         ",
+            "   ████████         ",
+        ],
+        vec![
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:200,g:150,b:110}; 20],
+            vec![Color::Rgb{r:180,g:130,b:95}; 20],
+            vec![Color::Rgb{r:160,g:115,b:85}; 20],
+        ]
+    )
+}
+
+// ===== CHAINSAW SPRITES =====
+
+fn get_chainsaw_idle() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "        ████        ",
+            "        ████        ",
+            "        ████        ",
+            "       ██████       ",
+            "     ██████████     ",
+            "    ████████████    ",
+            "     ██████████     ",
+        ],
+        vec![
+            vec![Color::Rgb{r:160,g:160,b:170}; 20], // Blade
+            vec![Color::Rgb{r:140,g:140,b:150}; 20],
+            vec![Color::Rgb{r:120,g:120,b:130}; 20],
+            vec![Color::Rgb{r:90,g:90,b:90}; 20],    // Guard
+            vec![Color::Rgb{r:200,g:120,b:30}; 20],  // Orange motor casing
+            vec![Color::Rgb{r:180,g:100,b:25}; 20],
+            vec![Color::Rgb{r:60,g:60,b:60}; 20],    // Handle
+        ]
+    )
+}
+
+fn get_chainsaw_rip() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "       ██████       ",
+            "        ████        ",
+            "       ██████       ",
+            "       ██████       ",
+            "     ██████████     ",
+            "    ████████████    ",
+            "     ██████████     ",
+        ],
+        vec![
+            vec![Color::Rgb{r:255,g:230,b:120}; 20], // Sparks on the chain
+            vec![Color::Rgb{r:170,g:170,b:180}; 20],
+            vec![Color::Rgb{r:150,g:150,b:160}; 20],
+            vec![Color::Rgb{r:90,g:90,b:90}; 20],
+            vec![Color::Rgb{r:210,g:130,b:35}; 20],
+            vec![Color::Rgb{r:180,g:100,b:25}; 20],
+            vec![Color::Rgb{r:60,g:60,b:60}; 20],
+        ]
+    )
+}
+
 // ===== PISTOL SPRITES =====
 
 fn get_pistol_idle() -> WeaponSprite {
@@ -458,4 +720,123 @@ fn get_gatling_reloading() -> WeaponSprite {
             vec![Color::Rgb{r:50,g:50,b:50}; 20],
         ]
     )
-}
\ No newline at end of file
+}
+
+// ===== ROCKET LAUNCHER SPRITES =====
+
+fn get_rocket_idle() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "      ████████      ",
+            "    ████████████    ",
+            "    ████████████    ",
+            "    ████████████    ",
+            "   ██████████████   ",
+            "   ████████████     ",
+            "     ████████       ",
+        ],
+        vec![
+            vec![Color::Rgb{r:30,g:30,b:30}; 20],    // Dark tube opening
+            vec![Color::Rgb{r:70,g:90,b:60}; 20],    // Olive tube
+            vec![Color::Rgb{r:80,g:100,b:70}; 20],
+            vec![Color::Rgb{r:70,g:90,b:60}; 20],
+            vec![Color::Rgb{r:90,g:90,b:90}; 20],    // Metal body
+            vec![Color::Rgb{r:60,g:60,b:60}; 20],
+            vec![Color::Rgb{r:50,g:50,b:50}; 20],
+        ]
+    )
+}
+
+fn get_rocket_firing() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "   ██████████████   ",
+            "  ████████████████  ",
+            "    ████████████    ",
+            "    ████████████    ",
+            "   ██████████████   ",
+            "   ████████████     ",
+            "     ████████       ",
+        ],
+        vec![
+            vec![Color::Rgb{r:255,g:230,b:120}; 20], // Launch flash
+            vec![Color::Rgb{r:255,g:140,b:40}; 20],  // Exhaust
+            vec![Color::Rgb{r:80,g:100,b:70}; 20],
+            vec![Color::Rgb{r:70,g:90,b:60}; 20],
+            vec![Color::Rgb{r:90,g:90,b:90}; 20],
+            vec![Color::Rgb{r:60,g:60,b:60}; 20],
+            vec![Color::Rgb{r:50,g:50,b:50}; 20],
+        ]
+    )
+}
+
+fn get_rocket_recoil() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "                    ",
+            "                    ",
+            "      ████████      ",
+            "    ████████████    ",
+            "    ████████████    ",
+            "   ██████████████   ",
+            "     ████████       ",
+        ],
+        vec![
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:30,g:30,b:30}; 20],
+            vec![Color::Rgb{r:70,g:90,b:60}; 20],
+            vec![Color::Rgb{r:60,g:80,b:50}; 20],
+            vec![Color::Rgb{r:80,g:80,b:80}; 20],
+            vec![Color::Rgb{r:50,g:50,b:50}; 20],
+        ]
+    )
+}
+
+fn get_rocket_reloading() -> WeaponSprite {
+    WeaponSprite::new(
+        vec![
+            "                    ",
+            "                    ",
+            "                    ",
+            "      ████████      ",
+            "    ████████████    ",
+            "   ██████████████   ",
+            "     ████████       ",
+        ],
+        vec![
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:0,g:0,b:0}; 20],
+            vec![Color::Rgb{r:160,g:60,b:40}; 20],   // Rocket warhead
+            vec![Color::Rgb{r:70,g:90,b:60}; 20],
+            vec![Color::Rgb{r:80,g:80,b:80}; 20],
+            vec![Color::Rgb{r:50,g:50,b:50}; 20],
+        ]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_definitions_are_valid() {
+        let file = parse_definitions(WEAPONS.built_in(), "built-in weapons").unwrap();
+        assert!(file.weapon.iter().any(|def| def.starting));
+        assert!(file.weapon.windows(2).all(|pair| pair[0].slot <= pair[1].slot));
+        assert!(file.weapon.iter().all(|def| def.sounds.cue(SoundCue::Fire).is_some()));
+    }
+
+    #[test]
+    fn unknown_sprite_is_rejected() {
+        let text = WEAPONS.built_in().replacen("sprite = \"pistol\"", "sprite = \"bfg\"", 1);
+        assert!(parse_definitions(&text, "test").is_err());
+    }
+
+    #[test]
+    fn duplicate_id_is_rejected() {
+        let text = WEAPONS.built_in().replacen("id = \"chainsaw\"", "id = \"fist\"", 1);
+        assert!(parse_definitions(&text, "test").is_err());
+    }
+}